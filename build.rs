@@ -0,0 +1,447 @@
+//! Generates the slide table from the slides in static/api/slide.
+//!
+//! The slides in static/api/slide make up the first deck, every folder in it is a deck
+//! of its own. Titles, slugs and tags come from the front matter of each slide, falling
+//! back to the first heading and the file name. Slides are ordered by the numeric suffix
+//! of their file name (page_3.html), which no two may share, unless an `index.txt` lists
+//! the files in order.
+//! The front matter may also set the duration, background, class, transition, section and
+//! the hidden and draft flags of a slide. The fragments and sub-slides the markers in a
+//! slide cut it into are counted, so the app can step through them, and the citations of
+//! a deck are listed in the order that numbers them.
+//! The table is written as a deck manifest to `$OUT_DIR/deck.json`, and the text of
+//! every slide goes into a search index at `$OUT_DIR/search.json`.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use ankan_common::{
+    citation,
+    front_matter::{self, FrontMatter},
+    markup::{self, strip_tags},
+    outline::{self, Section},
+    text::{self, slugify},
+};
+
+const SLIDE_DIR: &str = "static/api/slide";
+const INDEX_FILE: &str = "index.txt";
+const DECK_FILE: &str = "deck.txt";
+const DEFAULT_DECK_ID: &str = "rust";
+const URL_PREFIX: &str = "/api/slide";
+const TRANSITIONS: &[&str] = &["none", "fade", "slide", "zoom"];
+
+struct SlideFile {
+    file_name: String,
+    number: Option<u32>,
+    path: PathBuf,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SLIDE_DIR);
+
+    // The slides directory itself is the first deck, each folder in it another one
+    let root = Path::new(SLIDE_DIR);
+    let mut index = SearchIndex::default();
+    let mut decks = vec![build_deck(root, URL_PREFIX, DEFAULT_DECK_ID, &mut index)];
+    let mut folders: Vec<PathBuf> = fs::read_dir(root)
+        .unwrap_or_else(|e| fail(&format!("Could not read {}: {}", root.display(), e)))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    folders.sort();
+    for folder in folders {
+        let name = folder.file_name().unwrap().to_string_lossy().to_string();
+        decks.push(build_deck(
+            &folder,
+            &format!("{}/{}", URL_PREFIX, name),
+            &slugify(&name),
+            &mut index,
+        ));
+    }
+
+    let mut ids: HashSet<&str> = HashSet::new();
+    for deck in &decks {
+        let id = deck["id"].as_str().unwrap();
+        if !ids.insert(id) {
+            fail(&format!("Duplicate deck id '{}'", id));
+        }
+    }
+
+    let manifest = serde_json::json!({ "decks": decks });
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    write(&out_dir.join("deck.json"), &manifest);
+    write(&out_dir.join("search.json"), &index.to_json());
+}
+
+fn write(path: &Path, value: &serde_json::Value) {
+    fs::write(path, serde_json::to_string_pretty(value).unwrap())
+        .unwrap_or_else(|e| fail(&format!("Could not write {}: {}", path.display(), e)));
+}
+
+/// A deck is described by the front matter of the `deck.txt` in its folder,
+/// with `id`, `title`, `description`, `date`, `prefetch` and `loop` keys.
+fn build_deck(
+    dir: &Path,
+    url_prefix: &str,
+    default_id: &str,
+    index: &mut SearchIndex,
+) -> serde_json::Value {
+    let deck_file = dir.join(DECK_FILE);
+    println!("cargo:rerun-if-changed={}", deck_file.display());
+    let deck = match fs::read_to_string(&deck_file) {
+        Ok(text) => front_matter::parse(&text).0,
+        Err(_) => front_matter::FrontMatter::default(),
+    };
+
+    let id = deck.get("id").unwrap_or(default_id).to_string();
+    let title = deck.title.clone().unwrap_or_else(|| default_id.to_string());
+    let prefetch = deck.get("prefetch").map(|value| {
+        value.parse::<usize>().unwrap_or_else(|_| {
+            fail(&format!(
+                "Invalid prefetch '{}' in {}, expected a number of slides",
+                value,
+                deck_file.display()
+            ))
+        })
+    });
+    let loops = flag(&deck, "loop", &deck_file.display().to_string());
+    let files = ordered_slides(dir).unwrap_or_else(|e| fail(&e));
+
+    let mut slugs: HashMap<String, String> = HashMap::new();
+    let mut slides = vec![];
+    // Every citation of the deck in order, which numbers them
+    let mut cited = vec![];
+    for file in files {
+        println!("cargo:rerun-if-changed={}", file.path.display());
+        let text = fs::read_to_string(&file.path)
+            .unwrap_or_else(|e| fail(&format!("Could not read {}: {}", file.path.display(), e)));
+
+        let (front_matter, body) = front_matter::parse(&text);
+        let heading = first_heading(&file.file_name, body);
+        let slide_title = front_matter
+            .title
+            .clone()
+            .or_else(|| heading.clone())
+            .unwrap_or_else(|| match file.number {
+                Some(n) => format!("Slide {}", n),
+                None => stem(&file.file_name).to_string(),
+            });
+        let slug = front_matter.slug.clone().unwrap_or_else(|| match &heading {
+            Some(heading) => slugify(heading),
+            None => slugify(stem(&file.file_name)),
+        });
+
+        let aliases: Vec<&str> = front_matter
+            .get("aliases")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .collect();
+        // An alias may not hide a slug, or another alias, in the same deck
+        for name in std::iter::once(slug.as_str()).chain(aliases.iter().copied()) {
+            if let Some(other) = slugs.insert(name.to_string(), file.file_name.clone()) {
+                fail(&format!(
+                    "Duplicate slug '{}' used by both {} and {} in {}",
+                    name,
+                    other,
+                    file.file_name,
+                    dir.display()
+                ));
+            }
+        }
+
+        let location = file.path.display().to_string();
+        let duration = front_matter.get("duration").map(|value| {
+            front_matter::parse_duration(value)
+                .unwrap_or_else(|| fail(&format!("Invalid duration '{}' in {}", value, location)))
+        });
+        let transition = front_matter.get("transition").unwrap_or("none");
+        if !TRANSITIONS.contains(&transition) {
+            fail(&format!(
+                "Unknown transition '{}' in {}, expected one of {}",
+                transition,
+                location,
+                TRANSITIONS.join(", ")
+            ));
+        }
+        let hidden = flag(&front_matter, "hidden", &location);
+        let draft = flag(&front_matter, "draft", &location);
+        // Fragments revealed on the slide and on each sub-slide beneath it
+        let fragments: Vec<usize> = markup::steps(body)
+            .iter()
+            .map(|fragments| fragments.len() - 1)
+            .collect();
+        if file.file_name.ends_with(".md") {
+            cited.extend(citation::in_markdown(body));
+        } else {
+            cited.extend(citation::in_html(body));
+        }
+
+        // Drafts aren't ready to be found
+        if !draft {
+            index.add(
+                serde_json::json!({
+                    "deck": id,
+                    "deck_title": title,
+                    "slug": slug,
+                    "title": slide_title,
+                }),
+                &slide_title,
+                &front_matter.tags,
+                sections(&file.file_name, body),
+            );
+        }
+        slides.push(serde_json::json!({
+            "slug": slug,
+            "title": slide_title,
+            "tags": front_matter.tags,
+            "url": format!("{}/{}", url_prefix, file.file_name),
+            "duration": duration,
+            "background": front_matter.get("background"),
+            "class": front_matter.get("class"),
+            "transition": transition,
+            "section": front_matter.get("section"),
+            "fragments": fragments,
+            "hidden": hidden,
+            "draft": draft,
+            "aliases": aliases,
+        }));
+    }
+
+    serde_json::json!({
+        "id": id,
+        "title": title,
+        "description": deck.get("description"),
+        "date": deck.get("date"),
+        "prefetch": prefetch,
+        "loop": loops,
+        "citations": citation::first_cited(cited),
+        "slides": slides,
+    })
+}
+
+fn flag(front_matter: &FrontMatter, key: &str, location: &str) -> bool {
+    match front_matter.get(key) {
+        None | Some("false") | Some("no") => false,
+        Some("true") | Some("yes") | Some("") => true,
+        Some(value) => fail(&format!("Invalid {} '{}' in {}", key, value, location)),
+    }
+}
+
+fn ordered_slides(dir: &Path) -> Result<Vec<SlideFile>, String> {
+    let index = dir.join(INDEX_FILE);
+    if index.exists() {
+        println!("cargo:rerun-if-changed={}", index.display());
+        let listed = fs::read_to_string(&index)
+            .map_err(|e| format!("Could not read {}: {}", index.display(), e))?;
+        return listed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|file_name| {
+                let path = dir.join(file_name);
+                if path.is_file() {
+                    Ok(slide_file(file_name, path))
+                } else {
+                    Err(format!(
+                        "{} lists {}, which does not exist",
+                        index.display(),
+                        file_name
+                    ))
+                }
+            })
+            .collect();
+    }
+
+    let mut files = vec![];
+    for entry in
+        fs::read_dir(dir).map_err(|e| format!("Could not read {}: {}", dir.display(), e))?
+    {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+        if path.is_file() && (file_name.ends_with(".md") || file_name.ends_with(".html")) {
+            files.push(slide_file(&file_name, path));
+        }
+    }
+    // Slides without a number go last, in the order of their file names
+    files.sort_by(|a, b| {
+        (a.number.is_none(), a.number, &a.file_name).cmp(&(
+            b.number.is_none(),
+            b.number,
+            &b.file_name,
+        ))
+    });
+    // page_1.md and page_1.html have no order between them, only an index.txt can give one
+    for pair in files.windows(2) {
+        if pair[0].number.is_some() && pair[0].number == pair[1].number {
+            return Err(format!(
+                "{} and {} in {} have the same number, rename one or list the slides in an {}",
+                pair[0].file_name,
+                pair[1].file_name,
+                dir.display(),
+                INDEX_FILE
+            ));
+        }
+    }
+    Ok(files)
+}
+
+fn slide_file(file_name: &str, path: PathBuf) -> SlideFile {
+    let number = stem(file_name)
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|digits| digits.parse().ok());
+    SlideFile {
+        file_name: file_name.to_string(),
+        number,
+        path,
+    }
+}
+
+fn stem(file_name: &str) -> &str {
+    file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+}
+
+/// The text of the first heading, `# Title` in markdown or `<h1>Title</h1>` in HTML
+fn first_heading(file_name: &str, body: &str) -> Option<String> {
+    let heading = if file_name.ends_with(".md") {
+        outline::first_heading(body)
+    } else {
+        let start = (1..=6)
+            .filter_map(|n| body.find(&format!("<h{}", n)))
+            .min()?;
+        let content = &body[start..];
+        let content = &content[content.find('>')? + 1..];
+        let end = content.find("</h")?;
+        Some(strip_tags(&content[..end]))
+    };
+    heading.filter(|h| !h.is_empty())
+}
+
+/// Split the body of a slide at its headings, into plain text
+fn sections(file_name: &str, body: &str) -> Vec<Section> {
+    let mut sections = vec![];
+    let mut heading = None;
+    let mut push = |heading: Option<String>, text: &str| {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if heading.is_some() || !text.is_empty() {
+            sections.push(Section { heading, text });
+        }
+    };
+
+    if file_name.ends_with(".md") {
+        for section in outline::sections(body) {
+            push(section.heading, &section.text);
+        }
+    } else {
+        let mut rest = body;
+        while let Some(start) = find_heading(rest) {
+            push(heading.take(), &strip_tags(&rest[..start]));
+            let content = &rest[start..];
+            let open = content.find('>').map_or(content.len(), |i| i + 1);
+            let close = content[open..]
+                .find("</h")
+                .map_or(content.len(), |i| open + i);
+            heading = Some(strip_tags(&content[open..close]));
+            let end = content[close..]
+                .find('>')
+                .map_or(content.len(), |i| close + i + 1);
+            rest = &content[end..];
+        }
+        push(heading, &strip_tags(rest));
+    }
+    if sections.is_empty() {
+        sections.push(Section::default());
+    }
+    sections
+}
+
+// Position of the next `<h1>` ... `<h6>` tag
+fn find_heading(html: &str) -> Option<usize> {
+    html.match_indices("<h")
+        .map(|(i, _)| i)
+        .find(|&i| matches!(html.as_bytes().get(i + 2), Some(b'1'..=b'6')))
+}
+
+/// Inverted index from the words of the slides to the sections using them.
+/// Words in a title, tag or heading weigh more than those in the text.
+#[derive(Default)]
+struct SearchIndex {
+    documents: Vec<serde_json::Value>,
+    terms: BTreeMap<String, BTreeMap<(usize, usize), u32>>,
+}
+
+impl SearchIndex {
+    const TITLE_WEIGHT: u32 = 10;
+    const TAG_WEIGHT: u32 = 5;
+    const HEADING_WEIGHT: u32 = 3;
+    const TEXT_WEIGHT: u32 = 1;
+
+    fn add(
+        &mut self,
+        mut document: serde_json::Value,
+        title: &str,
+        tags: &[String],
+        sections: Vec<Section>,
+    ) {
+        let doc = self.documents.len();
+        self.add_terms(title, doc, 0, Self::TITLE_WEIGHT);
+        for tag in tags {
+            self.add_terms(tag, doc, 0, Self::TAG_WEIGHT);
+        }
+        for (i, section) in sections.iter().enumerate() {
+            if let Some(heading) = &section.heading {
+                self.add_terms(heading, doc, i, Self::HEADING_WEIGHT);
+            }
+            self.add_terms(&section.text, doc, i, Self::TEXT_WEIGHT);
+        }
+        document["sections"] = sections
+            .into_iter()
+            .map(|section| {
+                serde_json::json!({
+                    "anchor": section.heading.as_deref().map(slugify),
+                    "heading": section.heading,
+                    "text": section.text,
+                })
+            })
+            .collect();
+        self.documents.push(document);
+    }
+
+    fn add_terms(&mut self, text: &str, doc: usize, section: usize, weight: u32) {
+        for term in text::tokenize(text) {
+            *self
+                .terms
+                .entry(term)
+                .or_default()
+                .entry((doc, section))
+                .or_insert(0) += weight;
+        }
+    }
+
+    /// `{"documents": [...], "terms": {"word": [[document, section, weight], ...]}}`
+    fn to_json(&self) -> serde_json::Value {
+        let terms: serde_json::Map<String, serde_json::Value> = self
+            .terms
+            .iter()
+            .map(|(term, postings)| {
+                let postings = postings
+                    .iter()
+                    .map(|((doc, section), weight)| serde_json::json!([doc, section, weight]))
+                    .collect();
+                (term.clone(), serde_json::Value::Array(postings))
+            })
+            .collect();
+        serde_json::json!({ "documents": self.documents, "terms": terms })
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1)
+}
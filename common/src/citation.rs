@@ -0,0 +1,131 @@
+/// Citations, `[@key]` in a slide, numbered by the order the deck first cites them in.
+///
+/// build.rs lists the keys a deck cites in its manifest, so the app can number them
+/// without fetching every slide, and the app and ankan-lint read them the same way.
+use pulldown_cmark::{BrokenLink, Event, Options, Parser, Tag};
+
+use crate::markup;
+
+/// Links produced for `[@key]` citations use this scheme
+pub const SCHEME: &str = "cite:";
+
+/// Turns `[@key]` into a link pulldown_cmark can hand back to us
+pub fn resolve(reference: &str) -> Option<(String, String)> {
+    let key = reference.strip_prefix('@')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((format!("{}{}", SCHEME, key), String::new()))
+}
+
+/// Every key cited in the markdown, in order of appearance
+pub fn in_markdown(markdown: &str) -> Vec<String> {
+    let mut callback =
+        |link: BrokenLink| resolve(link.reference).map(|(url, title)| (url.into(), title.into()));
+    Parser::new_with_broken_link_callback(markdown, Options::empty(), Some(&mut callback))
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link(_, dest, _)) => {
+                dest.strip_prefix(SCHEME).map(|key| key.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// A `[@key]` citation written out in plain text, such as a text node of an HTML slide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cited<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub key: &'a str,
+}
+
+/// Every citation in plain text, in order of appearance
+pub fn in_text(text: &str) -> Vec<Cited<'_>> {
+    let mut found = vec![];
+    let mut from = 0;
+    while let Some(pos) = text[from..].find("[@") {
+        let start = from + pos;
+        let reference = text[start + 1..].split(']').next().unwrap_or_default();
+        let end = start + 1 + reference.len();
+        match resolve(reference) {
+            Some(_) if end < text.len() && !reference.contains('[') => {
+                found.push(Cited {
+                    start,
+                    end: end + 1,
+                    key: &reference[1..],
+                });
+                from = end + 1;
+            }
+            _ => from = start + 2,
+        }
+    }
+    found
+}
+
+/// Every key cited in an HTML slide, in order of appearance. Code is left alone, as it
+/// is in markdown.
+pub fn in_html(html: &str) -> Vec<String> {
+    in_text(&markup::prose(html))
+        .into_iter()
+        .map(|cited| cited.key.to_string())
+        .collect()
+}
+
+/// The keys in the order they are first cited, each once
+pub fn first_cited<I: IntoIterator<Item = String>>(keys: I) -> Vec<String> {
+    let mut order: Vec<String> = vec![];
+    for key in keys {
+        if !order.contains(&key) {
+            order.push(key);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_citations_in_order() {
+        let markdown = "As [@rustbook] says, and [@nomicon] and [@rustbook] agree";
+        assert_eq!(
+            in_markdown(markdown),
+            vec!["rustbook", "nomicon", "rustbook"]
+        );
+    }
+
+    #[test]
+    fn links_and_code_are_not_citations() {
+        let markdown = "[the book](https://doc.rust-lang.org/book/), [@ key] and `[@rustbook]`";
+        assert!(in_markdown(markdown).is_empty());
+    }
+
+    #[test]
+    fn finds_citations_in_text() {
+        let text = "[@a] and [@ b], [@] or [@c d] but [@e]";
+        let found = in_text(text);
+        let keys: Vec<&str> = found.iter().map(|cited| cited.key).collect();
+        assert_eq!(keys, vec!["a", "e"]);
+        assert_eq!(&text[found[1].start..found[1].end], "[@e]");
+    }
+
+    #[test]
+    fn an_unclosed_citation_is_text() {
+        assert!(in_text("[@a").is_empty());
+        assert_eq!(in_text("[@a [@b]")[0].key, "b");
+    }
+
+    #[test]
+    fn html_citations_outside_code() {
+        let html = "<p>As <i>[@rustbook]</i> says</p><pre><code>[@x]</code></pre><code>[@y]</code>[@nomicon]";
+        assert_eq!(in_html(html), vec!["rustbook", "nomicon"]);
+    }
+
+    #[test]
+    fn numbers_keys_by_their_first_citation() {
+        let keys = vec!["b", "a", "b", "c"].into_iter().map(String::from);
+        assert_eq!(first_cited(keys), vec!["b", "a", "c"]);
+    }
+}
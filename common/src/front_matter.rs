@@ -0,0 +1,180 @@
+/// Front matter at the top of a slide.
+///
+/// Markdown slides use a `---` fenced block, HTML slides an HTML comment:
+///
+/// ```text
+/// ---
+/// title: Understanding Ownership
+/// slug: ownership
+/// tags: memory, basics
+/// ---
+/// ```
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub tags: Vec<String>,
+    /// Any other `key: value` lines
+    pub fields: BTreeMap<String, String>,
+}
+
+impl FrontMatter {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+}
+
+/// Split a slide into its front matter and the content following it
+pub fn parse(text: &str) -> (FrontMatter, &str) {
+    let (block, body) = match split(text) {
+        Some(split) => split,
+        None => return (FrontMatter::default(), text),
+    };
+
+    let mut front_matter = FrontMatter::default();
+    for line in block.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        match key {
+            "title" => front_matter.title = Some(unquote(value).to_string()),
+            "slug" => front_matter.slug = Some(unquote(value).to_string()),
+            "tags" => {
+                front_matter.tags = value
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split(',')
+                    .map(|tag| unquote(tag.trim()).to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            }
+            _ => {
+                front_matter
+                    .fields
+                    .insert(key.to_string(), unquote(value).to_string());
+            }
+        }
+    }
+    (front_matter, body)
+}
+
+/// The content of a slide without its front matter
+pub fn strip(text: &str) -> &str {
+    split(text).map_or(text, |(_, body)| body)
+}
+
+/// Seconds in a duration such as `90`, `90s`, `2m` or `1m30s`
+pub fn parse_duration(value: &str) -> Option<u32> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+    let (minutes, rest) = match value.split_once('m') {
+        Some((minutes, rest)) => (minutes.trim().parse::<u32>().ok()?, rest.trim()),
+        None => (0, value),
+    };
+    let seconds = match rest.strip_suffix('s') {
+        Some(seconds) => seconds.trim().parse::<u32>().ok()?,
+        None if rest.is_empty() => 0,
+        None => return None,
+    };
+    Some(minutes * 60 + seconds)
+}
+
+fn split(text: &str) -> Option<(&str, &str)> {
+    let trimmed = text.trim_start();
+    let (open, close) = if trimmed.starts_with("---") {
+        ("---", "\n---")
+    } else if trimmed.starts_with("<!--") {
+        ("<!--", "-->")
+    } else {
+        return None;
+    };
+    let rest = &trimmed[open.len()..];
+    let end = rest.find(close)?;
+    let block = &rest[..end];
+    let body = &rest[end + close.len()..];
+    // Drop the line break closing the block
+    let body = body.trim_start_matches([' ', '\t']);
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
+    Some((block, body))
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(['"', '\''])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_markdown_front_matter() {
+        let text = "---\ntitle: \"Understanding Ownership\"\nslug: ownership\ntags: [memory, 'basics', ]\nduration: 1m30s\n---\n# Ownership\n";
+        let (front_matter, body) = parse(text);
+        assert_eq!(
+            front_matter.title.as_deref(),
+            Some("Understanding Ownership")
+        );
+        assert_eq!(front_matter.slug.as_deref(), Some("ownership"));
+        assert_eq!(front_matter.tags, vec!["memory", "basics"]);
+        assert_eq!(front_matter.get("duration"), Some("1m30s"));
+        assert_eq!(body, "# Ownership\n");
+    }
+
+    #[test]
+    fn parses_html_comment_front_matter() {
+        let text = "<!--\r\ntitle: Threads\r\nsection: Concurrency\r\n-->\r\n<div></div>";
+        let (front_matter, body) = parse(text);
+        assert_eq!(front_matter.title.as_deref(), Some("Threads"));
+        assert_eq!(front_matter.get("section"), Some("Concurrency"));
+        assert_eq!(body, "<div></div>");
+    }
+
+    #[test]
+    fn values_may_hold_colons() {
+        let (front_matter, _) = parse("---\nbackground: https://example.com/a.png\n---\n");
+        assert_eq!(
+            front_matter.get("background"),
+            Some("https://example.com/a.png")
+        );
+    }
+
+    #[test]
+    fn a_slide_without_front_matter_is_all_body() {
+        let text = "# Title\n---\nnot: front matter\n";
+        assert_eq!(parse(text), (FrontMatter::default(), text));
+        assert_eq!(strip(text), text);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("2m"), Some(120));
+        assert_eq!(parse_duration("1m30s"), Some(90));
+        assert_eq!(parse_duration("1m 30s"), Some(90));
+        assert_eq!(parse_duration("0s"), Some(0));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for value in &["", "m", "s", "1h", "1m30", "-5", "1.5m", "ms", "1m2m"] {
+            assert_eq!(parse_duration(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn an_unclosed_block_is_not_front_matter() {
+        let text = "---\ntitle: Never closed\n";
+        assert_eq!(parse(text), (FrontMatter::default(), text));
+    }
+}
@@ -0,0 +1,10 @@
+//! What the app, build.rs and ankan-lint have to agree on about the slides.
+//!
+//! The front matter of a slide, what it cites, which of its links point outside the
+//! site, and the text, slugs and search terms made from it come out the same at build
+//! time, when linting and in the browser.
+pub mod citation;
+pub mod front_matter;
+pub mod markup;
+pub mod outline;
+pub mod text;
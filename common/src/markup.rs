@@ -0,0 +1,192 @@
+/// Slide markup helpers.
+///
+/// The app, the search index and the linter have to agree on which links point
+/// outside the site and what the text of a slide is.
+///
+/// Slug of the References slide, which the app adds to every deck citing anything
+pub const REFERENCES_SLUG: &str = "References";
+
+/// Starts a sub-slide, stacked beneath the part of the slide before it
+pub const SUB_SLIDE_MARKER: &str = "<!-- sub-slide -->";
+
+/// The part of a slide after it is revealed one step after the part before it
+pub const FRAGMENT_MARKER: &str = "<!-- fragment -->";
+
+/// The text of an HTML fragment, without its tags
+pub fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            // Zero width spaces sneak in when copying text from slides
+            '\u{200b}' => {}
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// The text of an HTML fragment like `strip_tags` gives it, leaving out what is in its
+/// code and pre elements
+pub fn prose(html: &str) -> String {
+    let mut text = String::new();
+    // How many code or pre elements we're in
+    let mut depth = 0usize;
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        let close = match rest[open..].find('>') {
+            Some(close) => open + close,
+            None => break,
+        };
+        if depth == 0 {
+            text.push_str(&rest[..open]);
+        }
+        let tag = &rest[open + 1..close];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default();
+        if name.eq_ignore_ascii_case("code") || name.eq_ignore_ascii_case("pre") {
+            if tag.starts_with('/') {
+                depth = depth.saturating_sub(1);
+            } else if !tag.ends_with('/') {
+                depth += 1;
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    if depth == 0 {
+        text.push_str(rest);
+    }
+    strip_tags(&text)
+}
+
+/// Whether the URL points outside the site: 'https://...', 'mailto:...', '//cdn...'
+/// and the like
+pub fn has_scheme(url: &str) -> bool {
+    url.starts_with("//") || scheme(url).is_some()
+}
+
+/// Whether a link or image in a slide may be followed, relative URLs and
+/// http, https and mailto ones are
+pub fn is_safe_url(url: &str) -> bool {
+    scheme(url).is_none_or(|scheme| matches!(scheme.as_str(), "http" | "https" | "mailto"))
+}
+
+// The lower case scheme of the URL, none for a relative one
+fn scheme(url: &str) -> Option<String> {
+    // Browsers ignore whitespace and control characters in the scheme
+    let url = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>();
+    let scheme = &url[..url.find(':')?];
+    // A ':' after any of these is part of a relative URL
+    if scheme.contains(['/', '?', '#']) {
+        None
+    } else {
+        Some(scheme.to_ascii_lowercase())
+    }
+}
+
+/// The body of a slide cut into its sub-slides, and each of those into the fragments
+/// revealed one after the other. Markers only count on a line of their own, outside of
+/// fenced code.
+pub fn steps(body: &str) -> Vec<Vec<&str>> {
+    let mut sub_slides = vec![];
+    let mut fragments = vec![];
+    let mut fence = None;
+    let (mut start, mut offset) = (0, 0);
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let opens = ["```", "~~~"]
+            .iter()
+            .copied()
+            .find(|m| trimmed.starts_with(m));
+        let is_marker = trimmed == FRAGMENT_MARKER || trimmed == SUB_SLIDE_MARKER;
+        match (fence, opens) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, None) if is_marker => {
+                fragments.push(&body[start..offset]);
+                start = offset + line.len();
+                if trimmed == SUB_SLIDE_MARKER {
+                    sub_slides.push(std::mem::take(&mut fragments));
+                }
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    fragments.push(&body[start..]);
+    sub_slides.push(fragments);
+    sub_slides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_urls() {
+        assert!(is_safe_url("https://www.rust-lang.org"));
+        assert!(is_safe_url("HTTP://example.com"));
+        assert!(is_safe_url("mailto:ferris@example.com"));
+        assert!(is_safe_url("/images/ferris.svg"));
+        assert!(is_safe_url("#ownership"));
+        assert!(is_safe_url("slides?at=1:2"));
+    }
+
+    #[test]
+    fn unsafe_urls() {
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url("JaVaScRiPt:alert(1)"));
+        // Browsers skip whitespace and control characters in the scheme
+        assert!(!is_safe_url(" java\tscript:alert(1)"));
+        assert!(!is_safe_url("data:text/html,<script>alert(1)</script>"));
+        assert!(!is_safe_url("vbscript:msgbox(1)"));
+    }
+
+    #[test]
+    fn prose_leaves_out_code() {
+        let html =
+            "<p>Use <code>Box&lt;T&gt;</code> &amp; <PRE class=\"x\">let a;</PRE>friends</p>";
+        assert_eq!(prose(html), "Use  & friends");
+    }
+
+    #[test]
+    fn a_slide_without_markers_is_one_step() {
+        assert_eq!(steps("# Title\ntext\n"), vec![vec!["# Title\ntext\n"]]);
+    }
+
+    #[test]
+    fn cuts_sub_slides_and_their_fragments() {
+        let body = "a\n<!-- fragment -->\nb\n  <!-- sub-slide -->\nc\n<!-- fragment -->\nd";
+        assert_eq!(steps(body), vec![vec!["a\n", "b\n"], vec!["c\n", "d"]]);
+    }
+
+    #[test]
+    fn markers_in_fenced_code_are_kept() {
+        let body = "```html\n<!-- fragment -->\n```\n<!-- fragment -->\nb\n";
+        assert_eq!(
+            steps(body),
+            vec![vec!["```html\n<!-- fragment -->\n```\n", "b\n"]]
+        );
+    }
+
+    #[test]
+    fn markers_sharing_a_line_with_content_are_kept() {
+        let body = "a <!-- fragment -->\nb\n";
+        assert_eq!(steps(body), vec![vec![body]]);
+    }
+}
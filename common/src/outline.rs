@@ -0,0 +1,115 @@
+/// The headings of a markdown slide and the text under each of them.
+///
+/// Headings are read from the markdown the way the app renders them, so the search
+/// index and the titles build.rs takes from them match the ids on the rendered slide.
+use pulldown_cmark::{BrokenLink, Event, Options, Parser, Tag};
+
+use crate::citation;
+
+/// A part of a slide, from one of its headings up to the next
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Section {
+    /// The text and code of the heading, none for the text before the first one
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+/// Split a markdown slide at its headings. The text before the first heading makes a
+/// section of its own when there is any.
+pub fn sections(markdown: &str) -> Vec<Section> {
+    let mut sections = vec![Section::default()];
+    let mut in_heading = false;
+    // The app shows a citation as its number, which the text leaves out
+    let mut in_citation = false;
+    let mut resolve_citation = |link: BrokenLink| {
+        citation::resolve(link.reference).map(|(url, title)| (url.into(), title.into()))
+    };
+    let parser = Parser::new_with_broken_link_callback(
+        markdown,
+        Options::empty(),
+        Some(&mut resolve_citation),
+    );
+    for event in parser {
+        let section = sections.last_mut().unwrap();
+        match event {
+            Event::Start(Tag::Link(_, dest, _)) if dest.starts_with(citation::SCHEME) => {
+                in_citation = true
+            }
+            Event::End(Tag::Link(..)) if in_citation => in_citation = false,
+            Event::Text(_) if in_citation => {}
+            Event::Start(Tag::Heading(_)) => {
+                in_heading = true;
+                sections.push(Section {
+                    heading: Some(String::new()),
+                    text: String::new(),
+                });
+            }
+            Event::End(Tag::Heading(_)) => in_heading = false,
+            Event::Text(text) | Event::Code(text) if in_heading => section
+                .heading
+                .get_or_insert_with(String::new)
+                .push_str(&text),
+            Event::Text(text) | Event::Code(text) => section.text.push_str(&text),
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => section.text.push('\n'),
+            _ => {}
+        }
+    }
+    let first = &sections[0];
+    if first.text.trim().is_empty() && sections.len() > 1 {
+        sections.remove(0);
+    }
+    sections
+}
+
+/// The text of the first heading of a markdown slide
+pub fn first_heading(markdown: &str) -> Option<String> {
+    sections(markdown)
+        .into_iter()
+        .find_map(|section| section.heading)
+        .map(|heading| heading.trim().to_string())
+        .filter(|heading| !heading.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_at_headings() {
+        let markdown = "Intro\n\n# Ownership\nEvery value\nhas an owner\n\n## Moves\nText\n";
+        let sections = sections(markdown);
+        let headings: Vec<Option<&str>> = sections.iter().map(|s| s.heading.as_deref()).collect();
+        assert_eq!(headings, vec![None, Some("Ownership"), Some("Moves")]);
+        assert_eq!(sections[0].text.trim(), "Intro");
+        assert_eq!(sections[1].text.trim(), "Every value\nhas an owner");
+    }
+
+    #[test]
+    fn headings_take_their_code_and_drop_their_markup() {
+        let markdown = "# The `Box<T>` *type*\n";
+        assert_eq!(first_heading(markdown).as_deref(), Some("The Box<T> type"));
+        assert_eq!(sections(markdown).len(), 1);
+    }
+
+    #[test]
+    fn citations_are_left_out() {
+        let markdown = "# Ownership [@rustbook]\nAs [@rustbook] puts it\n";
+        let sections = sections(markdown);
+        assert_eq!(sections[0].heading.as_deref(), Some("Ownership "));
+        assert_eq!(sections[0].text.trim(), "As  puts it");
+    }
+
+    #[test]
+    fn comments_in_code_are_not_headings() {
+        let markdown = "```sh\n# not a heading\n```\n\nSetext heading\n--------------\n";
+        assert_eq!(first_heading(markdown).as_deref(), Some("Setext heading"));
+        assert!(sections(markdown)[0].text.contains("# not a heading"));
+    }
+
+    #[test]
+    fn a_slide_without_headings_is_one_section() {
+        assert_eq!(first_heading("Just text"), None);
+        assert_eq!(sections("Just text")[0].heading, None);
+        assert_eq!(sections(""), vec![Section::default()]);
+    }
+}
@@ -0,0 +1,26 @@
+/// Plain text helpers.
+///
+/// Slugs and search terms have to come out the same at build time, where the search
+/// index is made, and in the browser, where slides are rendered and searched.
+///
+/// Lower case, URL safe slug made from a title, also used as the id of headings
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// The lower case words of a text, as they are put in and looked up in the search index.
+/// Identifiers such as `RefCell` or `do_something` are kept whole.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|word| word.trim_matches('_'))
+        .filter(|word| word.chars().count() > 1)
+        .map(str::to_lowercase)
+}
@@ -1,23 +1,33 @@
 use crate::{
+    components::search::SearchBox,
+    deck_store::{DeckState, DeckStore, FetchState, ManifestState, Position},
+    pages::glossary::GlossaryModel,
     pages::home::HomeModel,
     pages::not_found::PageNotFound,
     pages::slides::{SlideId, SlidesModel},
+    pages::tags::TagsModel,
     switch::UrlSwitch,
 };
 use crate::{
-    slides_data::SLIDES,
+    slides_data::Manifest,
     switch::{AppAnchor, AppRoute, AppRouter},
 };
+use std::rc::Rc;
 use yew::{prelude::*, services::ConsoleService};
 use yew_router::{prelude::*, switch::Permissive};
 
 pub enum Msg {
     ToggleNavbar,
+    StoreChanged(Rc<DeckState>),
 }
 
 pub struct AppModel {
     link: ComponentLink<Self>,
     navbar_active: bool,
+    deck_state: Rc<DeckState>,
+    _store: Box<dyn Bridge<DeckStore>>,
+    /// Set by `?preview` in the address, hidden slides and drafts are shown as well
+    preview: bool,
 }
 
 impl Component for AppModel {
@@ -25,9 +35,13 @@ impl Component for AppModel {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let store = DeckStore::bridge(link.callback(Msg::StoreChanged));
         AppModel {
             link,
             navbar_active: false,
+            deck_state: Rc::default(),
+            _store: store,
+            preview: is_preview(),
         }
     }
 
@@ -37,6 +51,10 @@ impl Component for AppModel {
                 self.navbar_active = !self.navbar_active;
                 true
             }
+            Msg::StoreChanged(state) => {
+                self.deck_state = state;
+                true
+            }
         }
     }
 
@@ -60,20 +78,64 @@ impl AppModel {
         let Self {
             ref link,
             navbar_active,
+            preview: navbar_preview,
             ..
         } = *self;
 
         let active_class = if navbar_active { "is-active" } else { "" };
 
-        let pages: Vec<Html> = SLIDES
+        let decks = match &self.deck_state.manifest {
+            ManifestState::Loaded(manifest) => manifest.decks.as_slice(),
+            _ => &[],
+        };
+        // The slide on screen, if any, is marked in the dropdowns
+        let position = self.deck_state.position;
+        let item_class = |active: bool| {
+            if active {
+                "navbar-item is-active"
+            } else {
+                "navbar-item"
+            }
+        };
+        let deck_items: Vec<Html> = decks
             .iter()
-            .map(|x| {
+            .enumerate()
+            .map(|(d, deck)| {
+                let active = position.is_some_and(|p| p.deck == d);
                 html! {
-                    <a class="navbar-item">
-                        <AppAnchor classes="navbar-item" route=AppRoute::SlidesName(x.slug.to_string())>
-                            { x.title }
-                        </AppAnchor>
-                    </a>
+                    <AppAnchor classes=item_class(active) route=AppRoute::Deck(deck.id.clone())>
+                        { &deck.title }
+                    </AppAnchor>
+                }
+            })
+            .collect();
+        let pages: Vec<Html> = decks
+            .iter()
+            .enumerate()
+            .map(|(d, deck)| {
+                let slides = deck.slides.iter().enumerate().filter(|(_, x)| x.is_listed(navbar_preview)).map(|(s, x)| {
+                    let active = position == Some(Position { deck: d, slide: s });
+                    // A slide on screen that couldn't be fetched is marked, with the reason
+                    let failed = match &self.deck_state.fetch {
+                        FetchState::Failed(error) if active => {
+                            html! { <span class="has-text-danger" title=error.to_string()>{ " ⚠" }</span> }
+                        }
+                        _ => html! {},
+                    };
+                    html! {
+                        <a class="navbar-item">
+                            <AppAnchor classes=item_class(active) route=AppRoute::DeckSlidesName(deck.id.clone(), x.slug.clone())>
+                                { &x.title }
+                                { failed }
+                            </AppAnchor>
+                        </a>
+                    }
+                });
+                html! {
+                    <>
+                        <p class="navbar-item has-text-weight-semibold">{ &deck.title }</p>
+                        { for slides }
+                    </>
                 }
             })
             .collect();
@@ -101,6 +163,15 @@ impl AppModel {
                             { "Home" }
                         </AppAnchor>
 
+                        <div class="navbar-item has-dropdown is-hoverable">
+                            <a class="navbar-link">
+                                { "Decks" }
+                            </a>
+                            <div class="navbar-dropdown">
+                                { for deck_items }
+                            </div>
+                        </div>
+
                         <div class="navbar-item has-dropdown is-hoverable">
                             <a class="navbar-link">
                                 { "Slides" }
@@ -109,6 +180,19 @@ impl AppModel {
                                 { for pages }
                             </div>
                         </div>
+
+                        <AppAnchor classes="navbar-item" route=AppRoute::Tags>
+                            { "Tags" }
+                        </AppAnchor>
+
+                        <AppAnchor classes="navbar-item" route=AppRoute::Glossary>
+                            { "Glossary" }
+                        </AppAnchor>
+                    </div>
+                    <div class="navbar-end">
+                        <div class="navbar-item">
+                            <SearchBox />
+                        </div>
                     </div>
                 </div>
             </nav>
@@ -116,10 +200,37 @@ impl AppModel {
     }
 
     fn get_main(&self) -> Html {
+        let manifest = match &self.deck_state.manifest {
+            ManifestState::Loaded(manifest) => manifest.clone(),
+            ManifestState::Loading => {
+                return html! {
+                    <div class="hero is-fullheight">
+                        <div class="hero-body">
+                            <progress class="progress is-small is-primary" max="100"></progress>
+                            <p class="subtitle">{ "Loading the deck..." }</p>
+                        </div>
+                    </div>
+                };
+            }
+            ManifestState::Failed(error) => {
+                return html! {
+                    <section class="hero is-danger is-bold is-large">
+                        <div class="hero-body">
+                            <div class="container">
+                                <h1 class="title">{ "Could not load the deck" }</h1>
+                                <h2 class="subtitle">{ error }</h2>
+                            </div>
+                        </div>
+                    </section>
+                };
+            }
+        };
+
+        let preview = self.preview;
         html! {
             <div class="hero is-fullheight">
                 <AppRouter
-                    render=AppRouter::render(Self::switch)
+                    render=AppRouter::render(move |switch| Self::switch(switch, manifest.clone(), preview))
                     redirect=AppRouter::redirect(|route: Route| {
                         AppRoute::PageNotFound(Permissive(Some(route.route))).into_switch()
                     })
@@ -143,23 +254,110 @@ impl AppModel {
         }
     }
 
-    fn switch(switch: UrlSwitch) -> Html {
+    fn switch(switch: UrlSwitch, manifest: Rc<Manifest>, preview: bool) -> Html {
         let route = switch.route();
         ConsoleService::info(format!("Switching to {:?}", &route).as_str());
+        let requested = route.clone().into_route().route;
 
         match route {
             AppRoute::Home => {
                 html! { <HomeModel/> }
             }
+            AppRoute::Deck(deck) => {
+                let first = manifest
+                    .deck_index(&deck)
+                    .and_then(|index| manifest.decks.get(index))
+                    .map_or(0, |found| SlidesModel::first_slide(found, preview));
+                Self::slides(
+                    manifest,
+                    &requested,
+                    Some(deck),
+                    SlideId::Num(first),
+                    None,
+                    preview,
+                )
+            }
+            AppRoute::DeckSlidesNumber(deck, n) => Self::slides(
+                manifest,
+                &requested,
+                Some(deck),
+                SlideId::Num(n),
+                None,
+                preview,
+            ),
+            AppRoute::DeckSlidesName(deck, n) => Self::slides(
+                manifest,
+                &requested,
+                Some(deck),
+                SlideId::Str(n),
+                None,
+                preview,
+            ),
+            // Routes from before there were several decks point into the first one
             AppRoute::SlidesNumber(n) => {
-                html! { <SlidesModel id=SlideId::Num(n) /> }
+                Self::slides(manifest, &requested, None, SlideId::Num(n), None, preview)
             }
             AppRoute::SlidesName(n) => {
-                html! { <SlidesModel id=SlideId::Str(n) /> }
+                Self::slides(manifest, &requested, None, SlideId::Str(n), None, preview)
+            }
+            AppRoute::TagSlides(tag, deck, n) => Self::slides(
+                manifest,
+                &requested,
+                Some(deck),
+                SlideId::Str(n),
+                Some(decode(&tag)),
+                preview,
+            ),
+            AppRoute::Tags => {
+                html! { <TagsModel manifest=manifest preview=preview /> }
+            }
+            AppRoute::Tag(tag) => {
+                html! { <TagsModel manifest=manifest tag=Some(decode(&tag)) preview=preview /> }
+            }
+            AppRoute::Glossary => {
+                html! { <GlossaryModel manifest=manifest /> }
             }
             AppRoute::PageNotFound(Permissive(route)) => {
                 html! { <PageNotFound route=route /> }
             }
         }
     }
+
+    fn slides(
+        manifest: Rc<Manifest>,
+        requested: &str,
+        deck: Option<String>,
+        id: SlideId,
+        tag: Option<String>,
+        preview: bool,
+    ) -> Html {
+        let deck = match deck {
+            Some(deck) => manifest.deck_index(&deck),
+            None => Some(0),
+        };
+        // An empty manifest has no first deck to fall back on
+        let found = deck
+            .and_then(|deck| manifest.decks.get(deck).map(|found| (deck, found)))
+            .filter(|(_, found)| SlidesModel::get_slide(found, &id).is_some());
+        let deck = match found {
+            Some((deck, _)) => deck,
+            None => return html! { <PageNotFound route=Some(requested.to_string()) /> },
+        };
+        html! { <SlidesModel deck=deck id=id manifest=manifest tag=tag preview=preview /> }
+    }
+}
+
+fn is_preview() -> bool {
+    let search = yew::utils::window().location().search().unwrap_or_default();
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .any(|pair| pair == "preview" || pair.starts_with("preview="))
+}
+
+// Tags may hold spaces and such, which arrive percent-encoded in the route
+fn decode(part: &str) -> String {
+    js_sys::decode_uri_component(part)
+        .map(String::from)
+        .unwrap_or_else(|_| part.to_string())
 }
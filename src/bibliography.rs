@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use yew::{
+    format::{Json, Nothing},
+    html,
+    services::{
+        fetch::{FetchTask, Request, Response},
+        FetchService,
+    },
+    Callback, Html,
+};
+
+/// The deck-level bibliography, also backing the generated References slide
+pub const BIBLIOGRAPHY_URL: &str = "/api/bibliography.json";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Reference {
+    pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl Reference {
+    /// One line description of the reference, e.g. for tooltips
+    pub fn describe(&self) -> String {
+        match &self.author {
+            Some(author) => format!("{}, {}", self.title, author),
+            None => self.title.clone(),
+        }
+    }
+}
+
+/// References by their citation key, loaded from `/api/bibliography.json`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Bibliography {
+    references: BTreeMap<String, Reference>,
+}
+
+impl Bibliography {
+    pub fn get(&self, key: &str) -> Option<&Reference> {
+        self.references.get(key)
+    }
+}
+
+/// Citation keys of the deck in order of first use, which gives their numbers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Citations {
+    order: Vec<String>,
+}
+
+impl Citations {
+    /// The citations of a deck as its manifest lists them, in order of first use
+    pub fn new(order: Vec<String>) -> Self {
+        Citations { order }
+    }
+
+    /// The number shown for a citation, starting at 1
+    pub fn number(&self, key: &str) -> Option<usize> {
+        self.order.iter().position(|k| k == key).map(|i| i + 1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.order.iter().map(String::as_str)
+    }
+}
+
+/// Id of a reference on the References slide
+pub fn reference_id(key: &str) -> String {
+    format!("ref-{}", key)
+}
+
+/// Content of the generated References slide
+pub fn view_references(bibliography: &Bibliography, citations: &Citations) -> Html {
+    let items = citations.iter().map(|key| {
+        let reference = match bibliography.get(key) {
+            Some(reference) => reference,
+            None => return html! { <li id=reference_id(key)>{ format!("Unknown reference '{}'", key) }</li> },
+        };
+        let title = match &reference.url {
+            Some(url) => html! { <a href=url.clone() target="_blank">{ &reference.title }</a> },
+            None => html! { <span class="font-italic">{ &reference.title }</span> },
+        };
+        let author = match &reference.author {
+            Some(author) => html! { <>{ ", " }{ author }</> },
+            None => html! {},
+        };
+        html! { <li id=reference_id(key)>{ title }{ author }</li> }
+    });
+
+    html! {
+        <div class="content references">
+            <ol>
+                { for items }
+            </ol>
+        </div>
+    }
+}
+
+pub fn fetch(callback: Callback<Result<Bibliography, anyhow::Error>>) -> FetchTask {
+    let request = Request::get(BIBLIOGRAPHY_URL)
+        .body(Nothing)
+        .expect("Could not build request.");
+    let callback = Callback::from(
+        move |response: Response<Json<Result<Bibliography, anyhow::Error>>>| {
+            let Json(body) = response.into_body();
+            callback.emit(body)
+        },
+    );
+    FetchService::fetch(request, callback).expect("failed to start request")
+}
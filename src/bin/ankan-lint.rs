@@ -0,0 +1,550 @@
+//! Checks the decks without a browser and prints what it finds as JSON.
+//!
+//! Run it from the root of the crate, `cargo run --bin ankan-lint [static dir]`.
+//! The decks are read from `static/api/deck.json` when one is deployed, otherwise from
+//! the manifest build.rs generates. The run fails when anything is reported as an error,
+//! warnings alone don't fail it.
+//!
+//! ```text
+//! {"diagnostics": [{"severity": "error", "code": "missing-image", "deck": "rust",
+//!   "slide": "ownership", "file": "static/api/slide/page_3.md", "line": 4,
+//!   "message": "..."}], "errors": 1, "warnings": 0}
+//! ```
+use std::{
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use ankan_common::{
+    citation, front_matter,
+    markup::{has_scheme, strip_tags, REFERENCES_SLUG},
+};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+
+const GENERATED_MANIFEST: &str = include_str!(concat!(env!("OUT_DIR"), "/deck.json"));
+const DEPLOYED_MANIFEST: &str = "api/deck.json";
+const BIBLIOGRAPHY: &str = "api/bibliography.json";
+const IMAGE_DIR: &str = "images";
+
+// Fence languages we know how to show, only rust is highlighted
+const KNOWN_LANGUAGES: &[&str] = &[
+    "rust", "toml", "text", "txt", "sh", "bash", "shell", "console", "json",
+];
+
+// A rough measure of what fits on a slide, in lines of about LINE_WIDTH characters
+const VIEWPORT_LINES: usize = 30;
+const LINE_WIDTH: usize = 80;
+const IMAGE_LINES: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    decks: Vec<Deck>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Deck {
+    id: String,
+    slides: Vec<Slide>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Slide {
+    slug: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    url: String,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+struct Diagnostic {
+    severity: Severity,
+    code: &'static str,
+    deck: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slide: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    message: String,
+}
+
+/// Where diagnostics are reported from, the slide being checked
+struct Location<'a> {
+    deck: &'a str,
+    slide: Option<&'a str>,
+    file: Option<&'a Path>,
+    // The whole file, for turning offsets into line numbers
+    text: &'a str,
+}
+
+impl Location<'_> {
+    fn diagnostic(
+        &self,
+        severity: Severity,
+        code: &'static str,
+        offset: Option<usize>,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            deck: self.deck.to_string(),
+            slide: self.slide.map(str::to_string),
+            file: self.file.map(|f| f.display().to_string()),
+            line: offset.map(|offset| line_of(self.text, offset)),
+            message,
+        }
+    }
+}
+
+struct Linter {
+    static_dir: PathBuf,
+    manifest: Manifest,
+    bibliography: HashSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+fn main() {
+    let static_dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| "static".to_string()));
+    let manifest = match load_manifest(&static_dir) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(2);
+        }
+    };
+    let bibliography = fs::read_to_string(static_dir.join(BIBLIOGRAPHY))
+        .ok()
+        .and_then(|text| serde_json::from_str::<BTreeMap<String, serde_json::Value>>(&text).ok())
+        .map(|entries| entries.into_keys().collect())
+        .unwrap_or_default();
+
+    let mut linter = Linter {
+        static_dir,
+        manifest,
+        bibliography,
+        diagnostics: vec![],
+    };
+    linter.lint();
+
+    let errors = linter
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let report = serde_json::json!({
+        "diagnostics": linter.diagnostics,
+        "errors": errors,
+        "warnings": linter.diagnostics.len() - errors,
+    });
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    if errors > 0 {
+        process::exit(1);
+    }
+}
+
+fn load_manifest(static_dir: &Path) -> Result<Manifest, String> {
+    let deployed = static_dir.join(DEPLOYED_MANIFEST);
+    match fs::read_to_string(&deployed) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Could not parse {}: {}", deployed.display(), e)),
+        Err(_) => serde_json::from_str(GENERATED_MANIFEST)
+            .map_err(|e| format!("Could not parse the generated manifest: {}", e)),
+    }
+}
+
+impl Linter {
+    fn lint(&mut self) {
+        let mut deck_ids = HashSet::new();
+        for deck in &self.manifest.decks {
+            let location = Location {
+                deck: &deck.id,
+                slide: None,
+                file: None,
+                text: "",
+            };
+            if !deck_ids.insert(&deck.id) {
+                self.diagnostics.push(location.diagnostic(
+                    Severity::Error,
+                    "duplicate-deck",
+                    None,
+                    format!("Deck id '{}' is used by more than one deck", deck.id),
+                ));
+            }
+
+            let mut slugs = HashSet::new();
+            for slide in &deck.slides {
+                let location = Location {
+                    slide: Some(&slide.slug),
+                    ..location
+                };
+                for name in std::iter::once(&slide.slug).chain(&slide.aliases) {
+                    if !slugs.insert(name) {
+                        self.diagnostics.push(location.diagnostic(
+                            Severity::Error,
+                            "duplicate-slug",
+                            None,
+                            format!("Slug '{}' is used by more than one slide", name),
+                        ));
+                    }
+                    if !is_url_safe(name) {
+                        self.diagnostics.push(location.diagnostic(
+                            Severity::Error,
+                            "unsafe-slug",
+                            None,
+                            format!("Slug '{}' may only hold letters, digits, '-' and '_'", name),
+                        ));
+                    }
+                }
+                self.diagnostics
+                    .extend(self.lint_slide(&deck.id, slide, &location));
+            }
+        }
+    }
+
+    fn lint_slide(&self, deck: &str, slide: &Slide, location: &Location) -> Vec<Diagnostic> {
+        if has_scheme(&slide.url) {
+            return vec![];
+        }
+        let path = self.static_path(&slide.url);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                return vec![location.diagnostic(
+                    Severity::Error,
+                    "missing-slide",
+                    None,
+                    format!("{} does not exist", path.display()),
+                )]
+            }
+        };
+        let location = Location {
+            file: Some(&path),
+            text: &text,
+            ..*location
+        };
+        // Offsets into the body are turned into offsets into the file
+        let body = front_matter::strip(&text);
+        let start = text.len() - body.len();
+
+        let mut lints = SlideLints {
+            linter: self,
+            deck,
+            location: &location,
+            start,
+            diagnostics: vec![],
+        };
+        let lines = if is_html(slide) {
+            lints.html(body)
+        } else {
+            lints.markdown(body)
+        };
+        if lines > VIEWPORT_LINES {
+            lints.diagnostics.push(location.diagnostic(
+                Severity::Warning,
+                "overflow",
+                None,
+                format!(
+                    "The slide takes about {} lines, more than the {} that fit on screen",
+                    lines, VIEWPORT_LINES
+                ),
+            ));
+        }
+        lints.diagnostics
+    }
+
+    fn static_path(&self, url: &str) -> PathBuf {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        self.static_dir.join(path.trim_start_matches('/'))
+    }
+
+    /// Whether a link without a scheme leads to a page of the app or a file we serve
+    fn resolves(&self, link: &str) -> bool {
+        let path = link.split(['?', '#']).next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let decks = &self.manifest.decks;
+        let has_slide = |deck: &Deck, slide: &str| {
+            slide == REFERENCES_SLUG
+                || deck
+                    .slides
+                    .iter()
+                    .any(|s| s.slug == slide || s.aliases.iter().any(|a| a == slide))
+                || slide.parse().is_ok_and(|n: usize| n < deck.slides.len())
+        };
+        let deck = |id: &str| decks.iter().find(|deck| deck.id == id);
+        match segments.as_slice() {
+            [] | ["glossary"] | ["tags"] | ["tags", _] => true,
+            ["decks", id] => deck(id).is_some(),
+            ["decks", id, "slides", slide] | ["tags", _, "decks", id, "slides", slide] => {
+                deck(id).is_some_and(|deck| has_slide(deck, slide))
+            }
+            ["slides", slide] => decks.first().is_some_and(|deck| has_slide(deck, slide)),
+            _ => self.static_path(path).is_file(),
+        }
+    }
+}
+
+/// Lints of a single slide, they return how many lines the slide is estimated to take
+struct SlideLints<'a> {
+    linter: &'a Linter,
+    deck: &'a str,
+    location: &'a Location<'a>,
+    // Offset of the body in the file
+    start: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl SlideLints<'_> {
+    fn report(&mut self, severity: Severity, code: &'static str, offset: usize, message: String) {
+        let offset = Some(self.start + offset);
+        self.diagnostics
+            .push(self.location.diagnostic(severity, code, offset, message));
+    }
+
+    fn markdown(&mut self, body: &str) -> usize {
+        // Citations, [@key], are links to 'cite:key' like in the app
+        let mut resolve_citation = |link: pulldown_cmark::BrokenLink| {
+            citation::resolve(link.reference).map(|(url, title)| (url.into(), title.into()))
+        };
+        let parser = Parser::new_with_broken_link_callback(
+            body,
+            Options::empty(),
+            Some(&mut resolve_citation),
+        );
+
+        let mut lines = 0;
+        let mut in_code = false;
+        // Alt text of the image being read, with its source and offset
+        let mut image: Option<(String, usize, String)> = None;
+        for (event, range) in parser.into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    in_code = true;
+                    if let CodeBlockKind::Fenced(info) = kind {
+                        let language = info
+                            .split(|c: char| c == ',' || c.is_whitespace())
+                            .next()
+                            .unwrap_or_default();
+                        if !language.is_empty() && !KNOWN_LANGUAGES.contains(&language) {
+                            self.report(
+                                Severity::Warning,
+                                "unknown-language",
+                                range.start,
+                                format!("Code block in unknown language '{}'", language),
+                            );
+                        }
+                    }
+                }
+                Event::End(Tag::CodeBlock(_)) => in_code = false,
+                Event::Start(Tag::Image(_, src, _)) => {
+                    self.image(&src, range.start);
+                    image = Some((src.to_string(), range.start, String::new()));
+                    lines += IMAGE_LINES;
+                }
+                Event::End(Tag::Image(..)) => {
+                    if let Some((src, offset, alt)) = image.take() {
+                        self.alt(&src, offset, &alt);
+                    }
+                }
+                Event::Start(Tag::Link(_, dest, _)) => {
+                    if let Some(key) = dest.strip_prefix(citation::SCHEME) {
+                        self.citation(key, range.start);
+                    } else {
+                        self.link(&dest, range.start);
+                    }
+                }
+                Event::Start(Tag::Heading(_)) => lines += 1,
+                Event::Text(text) => match image.as_mut() {
+                    Some((_, _, alt)) => alt.push_str(&text),
+                    None if in_code => lines += text.lines().count(),
+                    None => lines += wrapped_lines(&text),
+                },
+                _ => {}
+            }
+        }
+        lines
+    }
+
+    fn html(&mut self, body: &str) -> usize {
+        let mut lines = 0;
+        for (offset, tag) in find_tags(body, "img") {
+            let src = attribute(tag, "src").unwrap_or_default();
+            self.image(&src, offset);
+            match attribute(tag, "alt") {
+                Some(alt) => self.alt(&src, offset, &alt),
+                None => self.alt(&src, offset, ""),
+            }
+            lines += IMAGE_LINES;
+        }
+        for (offset, tag) in find_tags(body, "a") {
+            if let Some(href) = attribute(tag, "href") {
+                self.link(&href, offset);
+            }
+        }
+        for key in citation::in_html(body) {
+            let offset = body.find(&format!("[@{}]", key)).unwrap_or_default();
+            self.citation(&key, offset);
+        }
+        // Markup has been indented along with the code, so only the text counts
+        let text = strip_tags(body);
+        lines += text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| wrapped_lines(line.trim()))
+            .sum::<usize>();
+        lines
+    }
+
+    fn citation(&mut self, key: &str, offset: usize) {
+        if !self.linter.bibliography.contains(key) {
+            self.report(
+                Severity::Error,
+                "unknown-citation",
+                offset,
+                format!("'{}' is not in the bibliography", key),
+            );
+        }
+    }
+
+    fn image(&mut self, src: &str, offset: usize) {
+        if src.is_empty() {
+            self.report(
+                Severity::Error,
+                "missing-image",
+                offset,
+                "Image without a source".to_string(),
+            );
+            return;
+        }
+        if has_scheme(src) {
+            return;
+        }
+        let path = self.linter.static_path(src);
+        if !path.is_file() {
+            self.report(
+                Severity::Error,
+                "missing-image",
+                offset,
+                format!("{} does not exist", path.display()),
+            );
+        } else if !path.starts_with(self.linter.static_dir.join(IMAGE_DIR)) {
+            self.report(
+                Severity::Warning,
+                "image-outside-images",
+                offset,
+                format!("{} is not in static/{}", src, IMAGE_DIR),
+            );
+        }
+    }
+
+    fn alt(&mut self, src: &str, offset: usize, alt: &str) {
+        if alt.trim().is_empty() {
+            self.report(
+                Severity::Warning,
+                "missing-alt",
+                offset,
+                format!("Image {} has no alt text", src),
+            );
+        }
+    }
+
+    fn link(&mut self, href: &str, offset: usize) {
+        if href.is_empty() || href.starts_with('#') || has_scheme(href) {
+            return;
+        }
+        if !self.linter.resolves(href) {
+            self.report(
+                Severity::Error,
+                "broken-link",
+                offset,
+                format!("Link to {} leads nowhere in deck '{}'", href, self.deck),
+            );
+        }
+    }
+}
+
+fn is_html(slide: &Slide) -> bool {
+    match slide.format.as_deref() {
+        Some("html") => true,
+        Some("markdown") => false,
+        _ => {
+            let path = slide.url.split(['?', '#']).next().unwrap_or_default();
+            path.ends_with(".html") || path.ends_with(".htm")
+        }
+    }
+}
+
+fn is_url_safe(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn wrapped_lines(text: &str) -> usize {
+    let chars = text.chars().count();
+    if chars == 0 {
+        0
+    } else {
+        chars.div_ceil(LINE_WIDTH)
+    }
+}
+
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// The offset and attribute part of every `<name ...>` tag
+fn find_tags<'a>(html: &'a str, name: &str) -> Vec<(usize, &'a str)> {
+    let open = format!("<{}", name);
+    html.match_indices(&open)
+        .filter_map(|(start, _)| {
+            let rest = &html[start + open.len()..];
+            // '<a' should not match '<abbr'
+            if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+                return None;
+            }
+            let end = rest.find('>')?;
+            Some((start, &rest[..end]))
+        })
+        .collect()
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let value = match after.strip_prefix('=') {
+            Some(value) => value.trim_start(),
+            None => continue,
+        };
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value
+                .split(char::is_whitespace)
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('/'),
+        };
+        return Some(value.to_string());
+    }
+    None
+}
@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+#[derive(Clone, Debug, Properties)]
+pub struct Props {
+    pub html: Rc<Html>,
+}
+
+// Only the pointer is compared, the tree itself is never walked
+impl PartialEq for Props {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.html, &other.html)
+    }
+}
+
+/// Mounts an already rendered tree, e.g. from the render cache.
+/// The tree is only cloned when a different one is handed in,
+/// not every time the parent renders.
+pub struct CachedHtml {
+    props: Props,
+}
+
+impl Component for CachedHtml {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        (*self.props.html).clone()
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use yew::{
+    prelude::*,
+    services::{timeout::TimeoutTask, ConsoleService, TimeoutService},
+    virtual_dom::VNode,
+};
+use yewtil::NeqAssign;
+
+use crate::{
+    export,
+    markdown::{self, CodeSource},
+};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub code: String,
+    pub language: Option<String>,
+    /// File name given in the fence info, e.g. ```` ```rust,main.rs ````
+    #[prop_or_default]
+    pub file_name: Option<String>,
+    /// Set on a main.rs block when the slide holds a Cargo.toml block too
+    #[prop_or_default]
+    pub cargo_toml: Option<String>,
+}
+
+pub enum Msg {
+    ToggleHidden,
+    Copy,
+    Copied(bool),
+    ResetCopied,
+    Download,
+    DownloadProject,
+}
+
+/// Renders a fenced code block with a toolbar for copying and downloading the code.
+/// Rust blocks follow the rustdoc convention where lines starting with `# `
+/// are hidden on the slide but kept as part of the full program.
+pub struct CodeBlock {
+    link: ComponentLink<Self>,
+    props: Props,
+    source: CodeSource,
+    show_hidden: bool,
+    copied: bool,
+    _reset_copied: Option<TimeoutTask>,
+}
+
+impl CodeBlock {
+    fn is_rust(&self) -> bool {
+        self.props.language.as_deref() == Some("rust")
+    }
+
+    fn source_for(props: &Props) -> CodeSource {
+        if props.language.as_deref() == Some("rust") {
+            markdown::split_hidden_lines(&props.code)
+        } else {
+            CodeSource {
+                visible: props.code.clone(),
+                full: props.code.clone(),
+                has_hidden: false,
+            }
+        }
+    }
+
+    fn view_code(&self) -> Html {
+        let code = if self.show_hidden {
+            &self.source.full
+        } else {
+            &self.source.visible
+        };
+        let el = yew::utils::document().create_element("code").unwrap();
+        el.set_inner_html(&markdown::colorize(code));
+        VNode::VRef(el.into())
+    }
+
+    fn download_name(&self) -> String {
+        if let Some(name) = &self.props.file_name {
+            return name.clone();
+        }
+        let extension = match self.props.language.as_deref() {
+            Some("rust") => "rs",
+            Some(lang) if lang.chars().all(|c| c.is_ascii_alphanumeric()) => lang,
+            _ => "txt",
+        };
+        format!("snippet.{}", extension)
+    }
+
+    fn view_toolbar(&self) -> Html {
+        let copy_label = if self.copied { "Copied!" } else { "Copy" };
+        let project = if self.props.cargo_toml.is_some() {
+            html! {
+                <button class="button is-small" title="Download as a cargo project"
+                    onclick=self.link.callback(|_| Msg::DownloadProject)>
+                    <span class="icon"><i class="fa fa-file-archive-o" aria-hidden="true"></i></span>
+                    <span>{ "Project" }</span>
+                </button>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="code-block__toolbar buttons has-addons">
+                <button class="button is-small" title="Copy to clipboard"
+                    onclick=self.link.callback(|_| Msg::Copy)>
+                    <span class="icon"><i class="fa fa-clipboard" aria-hidden="true"></i></span>
+                    <span>{ copy_label }</span>
+                </button>
+                <button class="button is-small" title=format!("Download {}", self.download_name())
+                    onclick=self.link.callback(|_| Msg::Download)>
+                    <span class="icon"><i class="fa fa-download" aria-hidden="true"></i></span>
+                    <span>{ "Download" }</span>
+                </button>
+                { project }
+            </div>
+        }
+    }
+
+    fn view_toggle(&self) -> Html {
+        if !self.source.has_hidden {
+            return html! {};
+        }
+        let label = if self.show_hidden {
+            "Hide boilerplate"
+        } else {
+            "Show hidden lines"
+        };
+        html! {
+            <button class="button is-small code-block__toggle"
+                aria-pressed=self.show_hidden.to_string()
+                onclick=self.link.callback(|_| Msg::ToggleHidden)>
+                { label }
+            </button>
+        }
+    }
+}
+
+impl Component for CodeBlock {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let source = Self::source_for(&props);
+        Self {
+            link,
+            props,
+            source,
+            show_hidden: false,
+            copied: false,
+            _reset_copied: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ToggleHidden => {
+                self.show_hidden = !self.show_hidden;
+                true
+            }
+            Msg::Copy => {
+                let code = self.source.full.clone();
+                let link = self.link.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = export::copy(&code).await;
+                    if let Err(e) = &result {
+                        ConsoleService::error(&format!("Could not copy code: {:?}", e));
+                    }
+                    link.send_message(Msg::Copied(result.is_ok()));
+                });
+                false
+            }
+            Msg::Copied(copied) => {
+                self.copied = copied;
+                self._reset_copied = Some(TimeoutService::spawn(
+                    Duration::from_secs(2),
+                    self.link.callback(|_| Msg::ResetCopied),
+                ));
+                true
+            }
+            Msg::ResetCopied => {
+                self.copied = false;
+                self._reset_copied = None;
+                true
+            }
+            Msg::Download => {
+                let name = self.download_name();
+                if let Err(e) = export::download(&name, self.source.full.as_bytes(), "text/plain") {
+                    ConsoleService::error(&format!("Could not download {}: {:?}", name, e));
+                }
+                false
+            }
+            Msg::DownloadProject => {
+                if let Some(cargo_toml) = &self.props.cargo_toml {
+                    let (name, zip) = export::cargo_project(&self.source.full, cargo_toml);
+                    if let Err(e) = export::download(&name, &zip, "application/zip") {
+                        ConsoleService::error(&format!("Could not download {}: {:?}", name, e));
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props.neq_assign(props) {
+            self.source = Self::source_for(&self.props);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let lang_class = if self.is_rust() { "language-rust" } else { "" };
+        html! {
+            <div class="code-block">
+                { self.view_toolbar() }
+                <pre class=lang_class>
+                    { self.view_code() }
+                </pre>
+                { self.view_toggle() }
+            </div>
+        }
+    }
+}
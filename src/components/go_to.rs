@@ -0,0 +1,134 @@
+use web_sys::{HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+const MAX_MATCHES: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// Titles of the slides in the deck, in order
+    pub titles: Vec<String>,
+    /// Called with the index of the chosen slide
+    pub on_select: Callback<usize>,
+    pub on_close: Callback<()>,
+}
+
+pub enum Msg {
+    Input(String),
+    KeyDown(KeyboardEvent),
+}
+
+/// Dialog for jumping to a slide by its number or part of its title
+pub struct GoTo {
+    link: ComponentLink<Self>,
+    props: Props,
+    query: String,
+    input: NodeRef,
+}
+
+impl Component for GoTo {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        GoTo {
+            link,
+            props,
+            query: String::new(),
+            input: NodeRef::default(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Input(query) => {
+                self.query = query;
+                true
+            }
+            Msg::KeyDown(event) => {
+                match event.key().as_str() {
+                    "Enter" => {
+                        if let Some(&index) = self.matches().first() {
+                            self.props.on_select.emit(index);
+                        }
+                    }
+                    "Escape" => self.props.on_close.emit(()),
+                    _ => {}
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            if let Some(input) = self.input.cast::<HtmlElement>() {
+                let _ = input.focus();
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let matches = self.matches().into_iter().map(|index| {
+            let on_select = self.props.on_select.reform(move |_| index);
+            html! {
+                <a class="panel-block" onclick=on_select>
+                    <span class="tag go-to__number">{ index + 1 }</span>
+                    { &self.props.titles[index] }
+                </a>
+            }
+        });
+        let on_close = self.props.on_close.reform(|_| ());
+        html! {
+            <div class="modal is-active go-to">
+                <div class="modal-background" onclick=on_close></div>
+                <div class="modal-content">
+                    <nav class="panel has-background-white">
+                        <div class="panel-block">
+                            <input
+                                class="input"
+                                type="text"
+                                placeholder="Slide number or title"
+                                aria-label="Slide number or title"
+                                ref=self.input.clone()
+                                value=&self.query
+                                oninput=self.link.callback(|e: InputData| Msg::Input(e.value))
+                                onkeydown=self.link.callback(Msg::KeyDown)
+                            />
+                        </div>
+                        { for matches }
+                    </nav>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl GoTo {
+    // Indices of the slides matching the query, a number picks that slide first
+    fn matches(&self) -> Vec<usize> {
+        let query = self.query.trim().to_lowercase();
+        let count = self.props.titles.len();
+        let by_number = query
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=count).contains(n))
+            .map(|n| n - 1);
+        let by_title = self
+            .props
+            .titles
+            .iter()
+            .enumerate()
+            .filter(|(_, title)| title.to_lowercase().contains(&query))
+            .map(|(index, _)| index);
+        by_number
+            .into_iter()
+            .chain(by_title.filter(|index| Some(*index) != by_number))
+            .take(MAX_MATCHES)
+            .collect()
+    }
+}
@@ -1 +1,8 @@
-
+pub mod cached_html;
+pub mod code_block;
+pub mod go_to;
+pub mod overview;
+pub mod progress_bar;
+pub mod search;
+pub mod shortcuts;
+pub mod slide_controls;
@@ -0,0 +1,214 @@
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent, ScrollIntoViewOptions, ScrollLogicalPosition};
+use yew::{
+    prelude::*,
+    services::keyboard::{KeyListenerHandle, KeyboardService},
+};
+
+use crate::{
+    components::cached_html::CachedHtml,
+    keymap::{Action, Keymap},
+};
+
+/// A slide as shown in the overview
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+    /// Index of the slide in the deck
+    pub index: usize,
+    pub title: String,
+    /// The rendered slide, none until its text has arrived
+    pub html: Option<Rc<Html>>,
+}
+
+#[derive(Clone, Debug, Properties)]
+pub struct Props {
+    pub thumbnails: Vec<Thumbnail>,
+    /// Index in the deck of the slide being presented
+    pub current: usize,
+    /// Called with the index in the deck of the chosen slide
+    pub on_select: Callback<usize>,
+    /// The keys of the slides, which move around the grid the same way
+    pub keymap: Keymap,
+}
+
+pub enum Msg {
+    KeyDown(KeyboardEvent),
+    Select(usize),
+}
+
+/// Grid of every slide of the deck, to pick one with the arrow keys or the pointer
+pub struct Overview {
+    link: ComponentLink<Self>,
+    props: Props,
+    // Position in the grid of the highlighted slide
+    selected: usize,
+    // Whether the highlighted slide is to be scrolled to once rendered
+    scroll: bool,
+    // How many slides make up a row, which depends on the width of the screen
+    columns: usize,
+    grid: NodeRef,
+    _keyboard_handle: KeyListenerHandle,
+}
+
+impl Component for Overview {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let keyboard_handle = KeyboardService::register_key_down(
+            &yew::utils::document(),
+            link.callback(Msg::KeyDown),
+        );
+        Overview {
+            selected: Self::position(&props),
+            scroll: true,
+            columns: 1,
+            link,
+            props,
+            grid: NodeRef::default(),
+            _keyboard_handle: keyboard_handle,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::KeyDown(event) => {
+                let last = self.props.thumbnails.len().saturating_sub(1);
+                let columns = self.columns;
+                let selected = match self.props.keymap.action(&event) {
+                    Some(Action::Previous) | Some(Action::PreviousSlide) => {
+                        self.selected.saturating_sub(1)
+                    }
+                    Some(Action::Next) | Some(Action::NextSlide) => (self.selected + 1).min(last),
+                    Some(Action::RowUp) | Some(Action::Up) => self.selected.saturating_sub(columns),
+                    Some(Action::RowDown) | Some(Action::Down) => {
+                        (self.selected + columns).min(last)
+                    }
+                    Some(Action::First) => 0,
+                    Some(Action::Last) => last,
+                    Some(Action::Jump) => {
+                        event.prevent_default();
+                        return self.update(Msg::Select(self.selected));
+                    }
+                    // Escape and the like close the overview through the slides
+                    _ => return false,
+                };
+                event.prevent_default();
+                let changed = selected != self.selected;
+                self.selected = selected;
+                self.scroll = changed;
+                changed
+            }
+            Msg::Select(position) => {
+                if let Some(thumbnail) = self.props.thumbnails.get(position) {
+                    self.props.on_select.emit(thumbnail.index);
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // The thumbnails compare by pointer in `CachedHtml`, so rendering again is cheap
+        if props.current != self.props.current {
+            self.selected = Self::position(&props);
+            self.scroll = true;
+        }
+        self.props = props;
+        self.selected = self
+            .selected
+            .min(self.props.thumbnails.len().saturating_sub(1));
+        true
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        self.columns = self.measure_columns();
+        if !std::mem::take(&mut self.scroll) {
+            return;
+        }
+        if let Some(element) = self.item(self.selected) {
+            let options = ScrollIntoViewOptions::new();
+            options.set_block(ScrollLogicalPosition::Nearest);
+            element.scroll_into_view_with_scroll_into_view_options(&options);
+        }
+    }
+
+    fn view(&self) -> Html {
+        let items = self
+            .props
+            .thumbnails
+            .iter()
+            .enumerate()
+            .map(|(position, thumbnail)| {
+                let selected = if position == self.selected {
+                    "is-selected"
+                } else {
+                    ""
+                };
+                let current = if thumbnail.index == self.props.current {
+                    "is-current"
+                } else {
+                    ""
+                };
+                let content = match &thumbnail.html {
+                    Some(html) => html! { <CachedHtml html=html.clone() /> },
+                    None => html! {},
+                };
+                html! {
+                    <a
+                        class=("overview__item", selected, current)
+                        onclick=self.link.callback(move |_| Msg::Select(position))
+                    >
+                        <div class="overview__slide">
+                            <div class="overview__content content">{ content }</div>
+                        </div>
+                        <p class="overview__title">
+                            <span class="tag is-rounded">{ position + 1 }</span>
+                            { &thumbnail.title }
+                        </p>
+                    </a>
+                }
+            });
+        html! {
+            <div class="overview" ref=self.grid.clone()>
+                { for items }
+            </div>
+        }
+    }
+}
+
+impl Overview {
+    // Where the slide being presented is in the grid
+    fn position(props: &Props) -> usize {
+        props
+            .thumbnails
+            .iter()
+            .position(|thumbnail| thumbnail.index == props.current)
+            .unwrap_or(0)
+    }
+
+    fn item(&self, position: usize) -> Option<HtmlElement> {
+        let grid = self.grid.cast::<HtmlElement>()?;
+        grid.children()
+            .item(position as u32)
+            .and_then(|item| item.dyn_into::<HtmlElement>().ok())
+    }
+
+    // How many columns the grid has laid out, one when that can't be told
+    fn measure_columns(&self) -> usize {
+        let grid = match self.grid.cast::<Element>() {
+            Some(grid) => grid,
+            None => return 1,
+        };
+        yew::utils::window()
+            .get_computed_style(&grid)
+            .ok()
+            .flatten()
+            .and_then(|style| style.get_property_value("grid-template-columns").ok())
+            .map(|columns| columns.split_whitespace().count())
+            .unwrap_or(1)
+            .max(1)
+    }
+}
@@ -0,0 +1,54 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::deck_store::{DeckState, DeckStore, FetchState};
+
+pub enum Msg {
+    StoreChanged(Rc<DeckState>),
+}
+
+/// Bar along the top of the page showing how far into the deck the slide on screen
+/// is, which pulses while the slide is being fetched
+pub struct ProgressBar {
+    state: Rc<DeckState>,
+    _store: Box<dyn Bridge<DeckStore>>,
+}
+
+impl Component for ProgressBar {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ProgressBar {
+            state: Rc::default(),
+            _store: DeckStore::bridge(link.callback(Msg::StoreChanged)),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::StoreChanged(state) => self.state.neq_assign(state),
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let (current, total) = self.state.progress;
+        let width = format!("width: {}%", current * 100 / total.max(1));
+        let fetching = if self.state.fetch == FetchState::Fetching {
+            "is-fetching"
+        } else {
+            ""
+        };
+        html! {
+            <div class="slides__progress" role="progressbar" aria-valuenow=current aria-valuemax=total>
+                <div class=("slides__progress-bar", fetching) style=width></div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use web_sys::KeyboardEvent;
+use yew::prelude::*;
+use yew_router::{agent::RouteRequest, prelude::*};
+
+use crate::{
+    search::{Hit, SearchIndex},
+    switch::AppRoute,
+};
+
+const MAX_RESULTS: usize = 8;
+
+pub enum Msg {
+    Input(String),
+    KeyDown(KeyboardEvent),
+    Open(String, String, Option<String>),
+}
+
+/// Search box for the navbar, listing the slides matching what's typed in it
+pub struct SearchBox {
+    link: ComponentLink<Self>,
+    route_dispatcher: RouteAgentDispatcher,
+    query: String,
+    // Parsed the first time something is searched for
+    index: Option<SearchIndex>,
+}
+
+impl Component for SearchBox {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        SearchBox {
+            link,
+            route_dispatcher: RouteAgentDispatcher::new(),
+            query: String::new(),
+            index: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Input(query) => {
+                if self.index.is_none() {
+                    self.index = Some(SearchIndex::generated());
+                }
+                self.query = query;
+                true
+            }
+            Msg::KeyDown(event) => match event.key().as_str() {
+                "Enter" => {
+                    let first = self.hits().into_iter().next().map(|hit| {
+                        Msg::Open(
+                            hit.document.deck.clone(),
+                            hit.document.slug.clone(),
+                            hit.section.anchor.clone(),
+                        )
+                    });
+                    match first {
+                        Some(msg) => self.update(msg),
+                        None => false,
+                    }
+                }
+                "Escape" => {
+                    self.query.clear();
+                    true
+                }
+                _ => false,
+            },
+            Msg::Open(deck, slug, anchor) => {
+                let mut route = AppRoute::DeckSlidesName(deck, slug).into_route();
+                // The slide scrolls to the heading once it has rendered
+                if let Some(anchor) = anchor {
+                    route.route.push('#');
+                    route.route.push_str(&anchor);
+                }
+                self.route_dispatcher.send(RouteRequest::ChangeRoute(route));
+                self.query.clear();
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let hits = self.hits();
+        let results = if self.query.trim().is_empty() {
+            html! {}
+        } else if hits.is_empty() {
+            html! {
+                <div class="dropdown-menu">
+                    <div class="dropdown-content">
+                        <p class="dropdown-item">{ "No slides found" }</p>
+                    </div>
+                </div>
+            }
+        } else {
+            html! {
+                <div class="dropdown-menu">
+                    <div class="dropdown-content">
+                        { for hits.iter().map(|hit| self.view_hit(hit)) }
+                    </div>
+                </div>
+            }
+        };
+        let active = if self.query.trim().is_empty() {
+            ""
+        } else {
+            "is-active"
+        };
+
+        html! {
+            <div class=("dropdown", "is-right", "search", active)>
+                <div class="dropdown-trigger control">
+                    <input
+                        class="input is-small"
+                        type="search"
+                        placeholder="Search slides"
+                        aria-label="Search slides"
+                        value=&self.query
+                        oninput=self.link.callback(|e: InputData| Msg::Input(e.value))
+                        onkeydown=self.link.callback(Msg::KeyDown)
+                    />
+                </div>
+                { results }
+            </div>
+        }
+    }
+}
+
+impl SearchBox {
+    fn hits(&self) -> Vec<Hit<'_>> {
+        match &self.index {
+            Some(index) => index.search(&self.query, MAX_RESULTS),
+            None => vec![],
+        }
+    }
+
+    fn view_hit(&self, hit: &Hit) -> Html {
+        let deck = hit.document.deck.clone();
+        let slug = hit.document.slug.clone();
+        let anchor = hit.section.anchor.clone();
+        let onclick = self
+            .link
+            .callback(move |_| Msg::Open(deck.clone(), slug.clone(), anchor.clone()));
+        let heading = match &hit.section.heading {
+            Some(heading) if heading != &hit.document.title => {
+                html! { <span class="search__heading">{ format!(" › {}", heading) }</span> }
+            }
+            _ => html! {},
+        };
+        html! {
+            <a class="dropdown-item search__result" onclick=onclick>
+                <p>
+                    <strong>{ &hit.document.title }</strong>
+                    { heading }
+                    <span class="search__deck">{ &hit.document.deck_title }</span>
+                </p>
+                <p class="search__snippet">
+                    { &hit.snippet.before }
+                    <mark>{ &hit.snippet.matched }</mark>
+                    { &hit.snippet.after }
+                </p>
+            </a>
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::keymap::{Action, Keymap};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub keymap: Keymap,
+    pub on_close: Callback<()>,
+}
+
+/// Overlay listing the keyboard shortcuts of the keymap in use
+pub struct Shortcuts {
+    props: Props,
+}
+
+impl Component for Shortcuts {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        let rows = self
+            .props
+            .keymap
+            .by_action()
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys = keys.into_iter().map(|key| html! { <kbd>{ key }</kbd> });
+                // The number is typed before the key
+                let prefix = if action == Action::Jump {
+                    html! { <><kbd>{ "0-9" }</kbd>{ " then " }</> }
+                } else {
+                    html! {}
+                };
+                html! {
+                    <tr>
+                        <td class="shortcuts__keys">{ prefix }{ for keys }</td>
+                        <td>{ action.describe() }</td>
+                    </tr>
+                }
+            });
+        let on_close = self.props.on_close.reform(|_| ());
+        html! {
+            <div class="modal is-active shortcuts">
+                <div class="modal-background" onclick=on_close.clone()></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ "Keyboard shortcuts" }</p>
+                        <button class="delete" aria-label="close" onclick=on_close></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <table class="table is-fullwidth">
+                            <tbody>{ for rows }</tbody>
+                        </table>
+                    </section>
+                </div>
+            </div>
+        }
+    }
+}
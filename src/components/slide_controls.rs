@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::deck_store::{DeckState, DeckStore, FetchState, ManifestState};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub on_back: Callback<()>,
+    pub on_forward: Callback<()>,
+}
+
+pub enum Msg {
+    StoreChanged(Rc<DeckState>),
+}
+
+/// Buttons stepping through the deck around a counter of the slides, which follow
+/// the slide on screen through the deck store
+pub struct SlideControls {
+    props: Props,
+    state: Rc<DeckState>,
+    _store: Box<dyn Bridge<DeckStore>>,
+}
+
+impl Component for SlideControls {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        SlideControls {
+            props,
+            state: Rc::default(),
+            _store: DeckStore::bridge(link.callback(Msg::StoreChanged)),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::StoreChanged(state) => self.state.neq_assign(state),
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        let (current, total) = self.state.progress;
+        let deck = match (&self.state.manifest, self.state.position) {
+            (ManifestState::Loaded(manifest), Some(position)) => manifest
+                .decks
+                .get(position.deck)
+                .map(|deck| (deck, position.slide)),
+            _ => None,
+        };
+        // A looping deck carries on past either end
+        let loops = deck.is_some_and(|(deck, _)| deck.loops);
+        let at_start = !loops && current <= 1;
+        let at_end = !loops && current >= total;
+        let title = deck
+            .and_then(|(deck, slide)| deck.slides.get(slide))
+            .map(|slide| slide.title.clone())
+            .unwrap_or_default();
+        // The counter tells when the slide is slow to come or didn't
+        let (status, title) = match &self.state.fetch {
+            FetchState::Done => ("", title),
+            FetchState::Fetching => ("is-fetching", format!("Loading {}", title)),
+            FetchState::Failed(error) => ("has-text-danger", error.to_string()),
+        };
+        html! {
+            <div class="slides__controls">
+                <button
+                    class="button is-rounded"
+                    aria-label="Previous slide"
+                    disabled=at_start
+                    onclick=self.props.on_back.reform(|_| ())
+                >
+                    { "‹" }
+                </button>
+                <span class=("slides__counter", status) title=title>
+                    { format!("{} / {}", current, total) }
+                </span>
+                <button
+                    class="button is-rounded"
+                    aria-label="Next slide"
+                    disabled=at_end
+                    onclick=self.props.on_forward.reform(|_| ())
+                >
+                    { "›" }
+                </button>
+            </div>
+        }
+    }
+}
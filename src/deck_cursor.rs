@@ -0,0 +1,528 @@
+/// Where we are in a deck and how moving through it works, without anything of the
+/// browser so it can be tested natively.
+///
+/// A position is a slide, one of the sub-slides stacked beneath it and the number of
+/// fragments of that sub-slide revealed so far. Slides belong to sections, which can
+/// be skipped between. Skipped slides, such as hidden ones, are never stepped onto but
+/// can still be gone to directly.
+///
+/// What happens when moving past either end of the deck
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Bounds {
+    /// Stay on the first or last slide
+    #[default]
+    Clamp,
+    /// Carry on from the other end
+    Loop,
+}
+
+/// The shape of a slide, as far as moving through it goes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlideLayout {
+    /// Fragment steps of the slide itself, then of each sub-slide beneath it
+    pub fragments: Vec<usize>,
+    pub section: usize,
+    /// Not stepped onto when moving through the deck
+    pub skipped: bool,
+}
+
+impl Default for SlideLayout {
+    fn default() -> Self {
+        SlideLayout {
+            fragments: vec![0],
+            section: 0,
+            skipped: false,
+        }
+    }
+}
+
+impl SlideLayout {
+    fn sub_slides(&self) -> usize {
+        self.fragments.len().max(1)
+    }
+
+    fn fragments(&self, vertical: usize) -> usize {
+        self.fragments.get(vertical).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CursorPosition {
+    pub slide: usize,
+    /// 0 is the slide itself, sub-slides beneath it count from 1
+    pub vertical: usize,
+    /// Fragments revealed on the sub-slide
+    pub fragment: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckCursor {
+    layout: Vec<SlideLayout>,
+    bounds: Bounds,
+    position: CursorPosition,
+}
+
+impl DeckCursor {
+    /// A cursor on the first slide that isn't skipped
+    pub fn new(layout: Vec<SlideLayout>, bounds: Bounds) -> Self {
+        let mut cursor = DeckCursor {
+            layout,
+            bounds,
+            position: CursorPosition::default(),
+        };
+        cursor.first();
+        cursor
+    }
+
+    pub fn position(&self) -> CursorPosition {
+        self.position
+    }
+
+    pub fn slide(&self) -> usize {
+        self.position.slide
+    }
+
+    pub fn section(&self) -> Option<usize> {
+        self.layout
+            .get(self.position.slide)
+            .map(|slide| slide.section)
+    }
+
+    /// The same position in a deck of another shape, e.g. once the skipped slides change.
+    /// The position is kept as far as the new layout allows.
+    pub fn relayout(&mut self, layout: Vec<SlideLayout>) {
+        self.layout = layout;
+        let position = self.position;
+        if self.goto(position.slide).is_none() {
+            self.first();
+        }
+        if let Some(slide) = self.layout.get(self.position.slide) {
+            let vertical = position.vertical.min(slide.sub_slides() - 1);
+            let fragment = position.fragment.min(slide.fragments(vertical));
+            self.position.vertical = vertical;
+            self.position.fragment = fragment;
+        }
+    }
+
+    /// Where `next` would go, none at the end of a clamped deck
+    pub fn peek_next(&self) -> Option<CursorPosition> {
+        let CursorPosition {
+            slide,
+            vertical,
+            fragment,
+        } = self.position;
+        let layout = self.layout.get(slide)?;
+        if fragment < layout.fragments(vertical) {
+            return Some(CursorPosition {
+                fragment: fragment + 1,
+                ..self.position
+            });
+        }
+        self.peek_next_slide()
+    }
+
+    /// Where `prev` would go, none at the start of a clamped deck
+    pub fn peek_prev(&self) -> Option<CursorPosition> {
+        if self.position.fragment > 0 {
+            return Some(CursorPosition {
+                fragment: self.position.fragment - 1,
+                ..self.position
+            });
+        }
+        // Going back lands on the previous slide as it was left, fully revealed
+        self.peek_prev_slide()
+            .map(|position| self.revealed(position.slide))
+    }
+
+    /// The next slide stepped onto, skipping any fragments left
+    pub fn peek_next_slide(&self) -> Option<CursorPosition> {
+        self.stepped_from(self.position.slide, true).map(Self::at)
+    }
+
+    pub fn peek_prev_slide(&self) -> Option<CursorPosition> {
+        self.stepped_from(self.position.slide, false).map(Self::at)
+    }
+
+    /// Reveal the next fragment, or else go to the next slide
+    pub fn next(&mut self) -> Option<CursorPosition> {
+        self.apply(self.peek_next())
+    }
+
+    /// Hide the last fragment revealed, or else go to the previous slide
+    pub fn prev(&mut self) -> Option<CursorPosition> {
+        self.apply(self.peek_prev())
+    }
+
+    pub fn next_slide(&mut self) -> Option<CursorPosition> {
+        self.apply(self.peek_next_slide())
+    }
+
+    pub fn prev_slide(&mut self) -> Option<CursorPosition> {
+        self.apply(self.peek_prev_slide())
+    }
+
+    /// The sub-slide beneath the current one
+    pub fn down(&mut self) -> Option<CursorPosition> {
+        let layout = self.layout.get(self.position.slide)?;
+        let vertical = self.position.vertical + 1;
+        if vertical >= layout.sub_slides() {
+            return None;
+        }
+        self.apply(Some(CursorPosition {
+            vertical,
+            fragment: 0,
+            ..self.position
+        }))
+    }
+
+    /// The sub-slide above the current one
+    pub fn up(&mut self) -> Option<CursorPosition> {
+        let vertical = self.position.vertical.checked_sub(1)?;
+        self.apply(Some(CursorPosition {
+            vertical,
+            fragment: 0,
+            ..self.position
+        }))
+    }
+
+    /// The first slide stepped onto in the following section
+    pub fn next_section(&mut self) -> Option<CursorPosition> {
+        let section = self.section()?;
+        let target = self
+            .stepped()
+            .find(|&slide| slide > self.position.slide && self.layout[slide].section != section)
+            .or_else(|| match self.bounds {
+                Bounds::Clamp => None,
+                Bounds::Loop => self
+                    .stepped()
+                    .find(|&slide| self.layout[slide].section != section),
+            })?;
+        self.apply(Some(Self::at(target)))
+    }
+
+    /// The first slide of the current section, or of the previous one when already there
+    pub fn prev_section(&mut self) -> Option<CursorPosition> {
+        let section = self.section()?;
+        let start = self.section_start(self.position.slide);
+        let target = if start < self.position.slide {
+            Some(start)
+        } else {
+            let before = self
+                .stepped()
+                .rfind(|&slide| slide < start && self.layout[slide].section != section)
+                .or_else(|| match self.bounds {
+                    Bounds::Clamp => None,
+                    Bounds::Loop => self
+                        .stepped()
+                        .rfind(|&slide| self.layout[slide].section != section),
+                });
+            before.map(|slide| self.section_start(slide))
+        }?;
+        self.apply(Some(Self::at(target)))
+    }
+
+    pub fn first(&mut self) -> Option<CursorPosition> {
+        let first = self.stepped().next()?;
+        self.apply(Some(Self::at(first)))
+    }
+
+    pub fn last(&mut self) -> Option<CursorPosition> {
+        let last = self.stepped().last()?;
+        self.apply(Some(Self::at(last)))
+    }
+
+    /// Go to a slide whether it's skipped or not, past the end it's clamped to the
+    /// last slide or looped back round
+    pub fn goto(&mut self, slide: usize) -> Option<CursorPosition> {
+        let len = self.layout.len();
+        if len == 0 {
+            return None;
+        }
+        let slide = match self.bounds {
+            Bounds::Clamp => slide.min(len - 1),
+            Bounds::Loop => slide % len,
+        };
+        self.apply(Some(Self::at(slide)))
+    }
+
+    /// Where the current slide falls among the slides stepped onto, counting from 1,
+    /// and how many there are. A skipped slide counts while it's shown.
+    pub fn progress(&self) -> (usize, usize) {
+        let slide = self.position.slide;
+        let before = self.stepped().take_while(|&s| s <= slide).count();
+        let total = self.stepped().count();
+        match self.layout.get(slide) {
+            Some(layout) if layout.skipped => (before + 1, total + 1),
+            _ => (before, total),
+        }
+    }
+
+    /// The slides stepped onto either side of the current one, nearest first
+    pub fn around(&self, depth: usize) -> Vec<usize> {
+        let slide = self.position.slide;
+        let after = self.stepped().filter(|&s| s > slide).take(depth);
+        let before = self.stepped().rev().filter(|&s| s < slide).take(depth);
+        after.chain(before).collect()
+    }
+
+    fn apply(&mut self, position: Option<CursorPosition>) -> Option<CursorPosition> {
+        let position = position?;
+        self.position = position;
+        Some(position)
+    }
+
+    fn at(slide: usize) -> CursorPosition {
+        CursorPosition {
+            slide,
+            ..CursorPosition::default()
+        }
+    }
+
+    // The slide with every fragment of its first sub-slide revealed
+    fn revealed(&self, slide: usize) -> CursorPosition {
+        CursorPosition {
+            slide,
+            vertical: 0,
+            fragment: self.layout[slide].fragments(0),
+        }
+    }
+
+    fn stepped(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        (0..self.layout.len()).filter(move |&slide| !self.layout[slide].skipped)
+    }
+
+    // The nearest slide stepped onto after or before the slide, looping if we may
+    fn stepped_from(&self, slide: usize, forward: bool) -> Option<usize> {
+        let found = if forward {
+            self.stepped().find(|&s| s > slide)
+        } else {
+            self.stepped().rev().find(|&s| s < slide)
+        };
+        match (found, self.bounds) {
+            (Some(found), _) => Some(found),
+            (None, Bounds::Clamp) => None,
+            (None, Bounds::Loop) => {
+                let wrapped = if forward {
+                    self.stepped().next()
+                } else {
+                    self.stepped().last()
+                };
+                wrapped.filter(|&s| s != slide)
+            }
+        }
+    }
+
+    // The first slide stepped onto in the section of the slide
+    fn section_start(&self, slide: usize) -> usize {
+        let section = self.layout[slide].section;
+        let mut start = slide;
+        for s in (0..slide).rev() {
+            if self.layout[s].section != section {
+                break;
+            }
+            if !self.layout[s].skipped {
+                start = s;
+            }
+        }
+        start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(count: usize) -> Vec<SlideLayout> {
+        vec![SlideLayout::default(); count]
+    }
+
+    fn skipping(skipped: &[usize], count: usize) -> Vec<SlideLayout> {
+        (0..count)
+            .map(|slide| SlideLayout {
+                skipped: skipped.contains(&slide),
+                ..SlideLayout::default()
+            })
+            .collect()
+    }
+
+    fn sections(sections: &[usize]) -> Vec<SlideLayout> {
+        sections
+            .iter()
+            .map(|&section| SlideLayout {
+                section,
+                ..SlideLayout::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn starts_on_the_first_slide_stepped_onto() {
+        let cursor = DeckCursor::new(skipping(&[0, 1], 4), Bounds::Clamp);
+        assert_eq!(cursor.slide(), 2);
+    }
+
+    #[test]
+    fn clamps_at_either_end() {
+        let mut cursor = DeckCursor::new(plain(3), Bounds::Clamp);
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.slide(), 0);
+        cursor.last();
+        assert_eq!(cursor.slide(), 2);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.slide(), 2);
+    }
+
+    #[test]
+    fn loops_round_either_end() {
+        let mut cursor = DeckCursor::new(plain(3), Bounds::Loop);
+        assert_eq!(cursor.prev().map(|p| p.slide), Some(2));
+        assert_eq!(cursor.next().map(|p| p.slide), Some(0));
+    }
+
+    #[test]
+    fn a_single_slide_does_not_loop_onto_itself() {
+        let mut cursor = DeckCursor::new(plain(1), Bounds::Loop);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.prev(), None);
+    }
+
+    #[test]
+    fn steps_over_skipped_slides() {
+        let mut cursor = DeckCursor::new(skipping(&[1, 2], 5), Bounds::Clamp);
+        assert_eq!(cursor.next().map(|p| p.slide), Some(3));
+        assert_eq!(cursor.prev().map(|p| p.slide), Some(0));
+        cursor.last();
+        assert_eq!(cursor.slide(), 4);
+    }
+
+    #[test]
+    fn goes_to_skipped_slides_directly() {
+        let mut cursor = DeckCursor::new(skipping(&[2], 4), Bounds::Clamp);
+        assert_eq!(cursor.goto(2).map(|p| p.slide), Some(2));
+        assert_eq!(cursor.progress(), (3, 4));
+        assert_eq!(cursor.next().map(|p| p.slide), Some(3));
+    }
+
+    #[test]
+    fn goto_clamps_or_loops_past_the_end() {
+        let mut clamped = DeckCursor::new(plain(4), Bounds::Clamp);
+        assert_eq!(clamped.goto(10).map(|p| p.slide), Some(3));
+        let mut looped = DeckCursor::new(plain(4), Bounds::Loop);
+        assert_eq!(looped.goto(5).map(|p| p.slide), Some(1));
+    }
+
+    #[test]
+    fn reveals_fragments_before_moving_on() {
+        let mut layout = plain(2);
+        layout[0].fragments = vec![2];
+        let mut cursor = DeckCursor::new(layout, Bounds::Clamp);
+        assert_eq!(cursor.next().map(|p| (p.slide, p.fragment)), Some((0, 1)));
+        assert_eq!(cursor.next().map(|p| (p.slide, p.fragment)), Some((0, 2)));
+        assert_eq!(cursor.next().map(|p| (p.slide, p.fragment)), Some((1, 0)));
+        // Back onto the slide with everything revealed, then hiding fragments again
+        assert_eq!(cursor.prev().map(|p| (p.slide, p.fragment)), Some((0, 2)));
+        assert_eq!(cursor.prev().map(|p| (p.slide, p.fragment)), Some((0, 1)));
+    }
+
+    #[test]
+    fn next_slide_skips_the_fragments_left() {
+        let mut layout = plain(2);
+        layout[0].fragments = vec![3];
+        let mut cursor = DeckCursor::new(layout, Bounds::Clamp);
+        assert_eq!(cursor.next_slide().map(|p| p.slide), Some(1));
+        assert_eq!(
+            cursor.prev_slide().map(|p| (p.slide, p.fragment)),
+            Some((0, 0))
+        );
+    }
+
+    #[test]
+    fn moves_through_sub_slides() {
+        let mut layout = plain(2);
+        layout[0].fragments = vec![0, 1, 0];
+        let mut cursor = DeckCursor::new(layout, Bounds::Clamp);
+        assert_eq!(cursor.up(), None);
+        assert_eq!(cursor.down().map(|p| p.vertical), Some(1));
+        assert_eq!(
+            cursor.next().map(|p| (p.vertical, p.fragment)),
+            Some((1, 1))
+        );
+        assert_eq!(
+            cursor.down().map(|p| (p.vertical, p.fragment)),
+            Some((2, 0))
+        );
+        assert_eq!(cursor.down(), None);
+        assert_eq!(cursor.up().map(|p| (p.vertical, p.fragment)), Some((1, 0)));
+        cursor.next();
+        // Moving sideways leaves the stack
+        assert_eq!(cursor.next().map(|p| (p.slide, p.vertical)), Some((1, 0)));
+    }
+
+    #[test]
+    fn moves_between_sections() {
+        let mut cursor = DeckCursor::new(sections(&[0, 0, 1, 1, 2]), Bounds::Clamp);
+        assert_eq!(cursor.section(), Some(0));
+        assert_eq!(cursor.next_section().map(|p| p.slide), Some(2));
+        assert_eq!(cursor.next_section().map(|p| p.slide), Some(4));
+        assert_eq!(cursor.next_section(), None);
+        assert_eq!(cursor.prev_section().map(|p| p.slide), Some(2));
+        cursor.goto(3);
+        // From inside a section back to its start, then to the one before
+        assert_eq!(cursor.prev_section().map(|p| p.slide), Some(2));
+        assert_eq!(cursor.prev_section().map(|p| p.slide), Some(0));
+        assert_eq!(cursor.prev_section(), None);
+    }
+
+    #[test]
+    fn sections_loop_when_the_deck_does() {
+        let mut cursor = DeckCursor::new(sections(&[0, 1, 1]), Bounds::Loop);
+        cursor.last();
+        assert_eq!(cursor.next_section().map(|p| p.slide), Some(0));
+        assert_eq!(cursor.prev_section().map(|p| p.slide), Some(1));
+    }
+
+    #[test]
+    fn progress_counts_the_slides_stepped_onto() {
+        let mut cursor = DeckCursor::new(skipping(&[1], 4), Bounds::Clamp);
+        assert_eq!(cursor.progress(), (1, 3));
+        cursor.last();
+        assert_eq!(cursor.progress(), (3, 3));
+    }
+
+    #[test]
+    fn lists_the_slides_around_the_current_one() {
+        let mut cursor = DeckCursor::new(skipping(&[3], 7), Bounds::Clamp);
+        cursor.goto(2);
+        assert_eq!(cursor.around(2), vec![4, 5, 1, 0]);
+    }
+
+    #[test]
+    fn relayout_keeps_the_position_where_it_can() {
+        let mut layout = plain(3);
+        layout[1].fragments = vec![2, 0];
+        let mut cursor = DeckCursor::new(layout, Bounds::Clamp);
+        cursor.goto(1);
+        cursor.next();
+        cursor.next();
+        cursor.relayout(plain(3));
+        assert_eq!(
+            cursor.position(),
+            CursorPosition {
+                slide: 1,
+                vertical: 0,
+                fragment: 0
+            }
+        );
+        cursor.relayout(plain(1));
+        assert_eq!(cursor.slide(), 0);
+    }
+
+    #[test]
+    fn an_empty_deck_goes_nowhere() {
+        let mut cursor = DeckCursor::new(vec![], Bounds::Loop);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.goto(3), None);
+        assert_eq!(cursor.first(), None);
+        assert_eq!(cursor.progress(), (0, 0));
+    }
+}
@@ -0,0 +1,143 @@
+/// The decks and where we are in them, shared by every component that cares.
+///
+/// The store loads the manifest once, the slides view tells it which slide is shown,
+/// how far into the deck that is and how fetching the slide is going. The navbar, the
+/// progress bar and the slide controls bridge to it to be told about every change,
+/// the slides view uses a dispatcher as it only reports to it.
+use std::{collections::HashSet, rc::Rc};
+
+use yew::{
+    agent::{Agent, AgentLink, Context, HandlerId},
+    services::{fetch::FetchTask, ConsoleService},
+};
+
+use crate::slides_data::{self, FetchError, Manifest};
+
+/// State of the deck manifest, which is fetched once at startup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ManifestState {
+    #[default]
+    Loading,
+    Loaded(Rc<Manifest>),
+    Failed(String),
+}
+
+/// A slide, by its index in the deck and the index of the deck in the manifest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub deck: usize,
+    pub slide: usize,
+}
+
+/// How getting the text of the slide at the position is going
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FetchState {
+    /// The slide is shown, or was served from the cache without a request
+    #[default]
+    Done,
+    Fetching,
+    Failed(FetchError),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeckState {
+    pub manifest: ManifestState,
+    /// The slide being presented, none when no slide is on screen
+    pub position: Option<Position>,
+    /// Where the slide falls among the slides stepped through, counting from 1,
+    /// and how many there are
+    pub progress: (usize, usize),
+    pub fetch: FetchState,
+}
+
+pub enum Request {
+    SetPosition(Position, (usize, usize)),
+    ClearPosition,
+    SetFetch(FetchState),
+}
+
+pub enum Msg {
+    ReceiveManifest(Result<Manifest, anyhow::Error>),
+}
+
+pub struct DeckStore {
+    link: AgentLink<Self>,
+    state: Rc<DeckState>,
+    subscribers: HashSet<HandlerId>,
+    _manifest_task: FetchTask,
+}
+
+impl Agent for DeckStore {
+    type Reach = Context<Self>;
+    type Message = Msg;
+    type Input = Request;
+    type Output = Rc<DeckState>;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        let task = slides_data::fetch_manifest(link.callback(Msg::ReceiveManifest));
+        DeckStore {
+            link,
+            state: Rc::new(DeckState::default()),
+            subscribers: HashSet::new(),
+            _manifest_task: task,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            Msg::ReceiveManifest(response) => {
+                let manifest = match response {
+                    Ok(manifest) => ManifestState::Loaded(Rc::new(manifest)),
+                    Err(error) => {
+                        // With the alternate format the cause follows the context
+                        let error = format!("{:#}", error);
+                        ConsoleService::error(&error);
+                        ManifestState::Failed(error)
+                    }
+                };
+                self.set(|state| state.manifest = manifest);
+            }
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        // Dispatchers only report to the store
+        if id.is_respondable() {
+            self.subscribers.insert(id);
+            self.link.respond(id, self.state.clone());
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
+        match msg {
+            Request::SetPosition(position, progress) => self.set(|state| {
+                state.position = Some(position);
+                state.progress = progress;
+            }),
+            Request::ClearPosition => self.set(|state| {
+                state.position = None;
+                state.progress = (0, 0);
+                state.fetch = FetchState::Done;
+            }),
+            Request::SetFetch(fetch) => self.set(|state| state.fetch = fetch),
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+}
+
+impl DeckStore {
+    // Change the state, and tell the subscribers when it did change
+    fn set(&mut self, change: impl FnOnce(&mut DeckState)) {
+        let mut state = (*self.state).clone();
+        change(&mut state);
+        if state != *self.state {
+            self.state = Rc::new(state);
+            for id in &self.subscribers {
+                self.link.respond(*id, self.state.clone());
+            }
+        }
+    }
+}
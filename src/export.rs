@@ -0,0 +1,94 @@
+/// Helpers for getting code out of the slides and onto the attendees machines.
+use wasm_bindgen::{prelude::*, JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlElement, Url};
+
+use crate::zip::ZipWriter;
+
+// How long the object URL of a download is kept alive
+const REVOKE_AFTER_MS: i32 = 10_000;
+
+/// Copy text to the clipboard, resolves once the text has been written
+pub async fn copy(text: &str) -> Result<(), JsValue> {
+    // Throws straight away where there's no clipboard, e.g. on an insecure origin
+    let promise = js_sys::Promise::from(copy_to_clipboard(text)?);
+    wasm_bindgen_futures::JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Let the browser download the given bytes as a file
+pub fn download(file_name: &str, data: &[u8], mime: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor = yew::utils::document().create_element("a")?;
+    anchor.set_attribute("href", &url)?;
+    anchor.set_attribute("download", file_name)?;
+    anchor.dyn_into::<HtmlElement>()?.click();
+
+    // Revoking the URL straight after the click can cancel the download in some browsers
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    yew::utils::window().set_timeout_with_callback_and_timeout_and_arguments_0(
+        revoke.unchecked_ref(),
+        REVOKE_AFTER_MS,
+    )?;
+    Ok(())
+}
+
+/// Bundle a main.rs and its Cargo.toml as a zipped cargo project.
+/// The project folder is named after the package, if the manifest names one.
+pub fn cargo_project(main_rs: &str, cargo_toml: &str) -> (String, Vec<u8>) {
+    let name = package_name(cargo_toml).unwrap_or("slide-snippet");
+    let mut zip = ZipWriter::new();
+    zip.add_file(&format!("{}/Cargo.toml", name), cargo_toml.as_bytes());
+    zip.add_file(&format!("{}/src/main.rs", name), main_rs.as_bytes());
+    (format!("{}.zip", name), zip.finish())
+}
+
+fn package_name(cargo_toml: &str) -> Option<&str> {
+    cargo_toml
+        .lines()
+        .skip_while(|line| line.trim() != "[package]")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            if key.trim() == "name" {
+                Some(value.trim().trim_matches('"'))
+            } else {
+                None
+            }
+        })
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = copyToClipboard)]
+    fn copy_to_clipboard(text: &str) -> Result<JsValue, JsValue>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_name_from_the_package_table() {
+        let cargo_toml = "[package]\nname = \"ferris-says\"\nversion = \"0.1.0\"\n";
+        assert_eq!(package_name(cargo_toml), Some("ferris-says"));
+    }
+
+    #[test]
+    fn package_name_ignores_other_tables() {
+        let cargo_toml = "[dependencies]\nname = \"nope\"\n\n[package]\nversion = \"0.1.0\"\n  name=\"late\"\n[lib]\nname = \"lib\"\n";
+        assert_eq!(package_name(cargo_toml), Some("late"));
+        assert_eq!(
+            package_name("[package]\nversion = \"0.1.0\"\n[lib]\nname = \"lib\"\n"),
+            None
+        );
+        assert_eq!(package_name("name = \"top\"\n"), None);
+    }
+}
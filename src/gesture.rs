@@ -0,0 +1,200 @@
+/// Swipes and taps on touch screens, which step through the deck.
+///
+/// A swipe has to travel far and fast enough and mostly sideways, so scrolling a long
+/// slide up and down isn't taken for one. A short tap near the left or right edge of
+/// the screen steps too, unless it lands on a link or a control.
+use wasm_bindgen::JsCast;
+use web_sys::{Element, PointerEvent};
+
+// Pixels a swipe has to travel sideways
+const SWIPE_DISTANCE: f64 = 50.0;
+// Pixels per millisecond a swipe has to reach
+const SWIPE_VELOCITY: f64 = 0.3;
+// How much more a swipe has to travel sideways than up or down
+const SWIPE_SLOPE: f64 = 2.0;
+// Pixels a tap may wander, and milliseconds it may last
+const TAP_DISTANCE: f64 = 10.0;
+const TAP_DURATION: f64 = 300.0;
+// Share of the screen width on either side that takes taps
+const EDGE_ZONE: f64 = 0.2;
+// Taps on these are left to them
+const INTERACTIVE: &str = "a, button, input, textarea, select, summary, label, [contenteditable]";
+
+/// Which way a gesture steps through the deck
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Back,
+    Forward,
+}
+
+/// Where and when a touch started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Start {
+    id: i32,
+    x: f64,
+    y: f64,
+    time: f64,
+}
+
+impl Start {
+    /// The start of a gesture, only touches and pens make them
+    pub fn new(event: &PointerEvent) -> Option<Self> {
+        if !event.is_primary() || event.pointer_type() == "mouse" {
+            return None;
+        }
+        Some(Start {
+            id: event.pointer_id(),
+            x: event.client_x() as f64,
+            y: event.client_y() as f64,
+            time: event.time_stamp(),
+        })
+    }
+
+    /// The gesture made, if any, when the pointer is lifted
+    pub fn end(self, event: &PointerEvent) -> Option<Gesture> {
+        if event.pointer_id() != self.id {
+            return None;
+        }
+        let width = yew::utils::window()
+            .inner_width()
+            .ok()
+            .and_then(|width| width.as_f64())
+            .unwrap_or_default();
+        self.classify(
+            event.client_x() as f64,
+            event.client_y() as f64,
+            event.time_stamp(),
+            width,
+            is_interactive(event),
+        )
+    }
+
+    // The gesture made by lifting the pointer at `x`, `y` and `time`, on a screen `width`
+    // pixels wide, `interactive` when it was lifted over a link or a control
+    fn classify(self, x: f64, y: f64, time: f64, width: f64, interactive: bool) -> Option<Gesture> {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        let duration = (time - self.time).max(1.0);
+
+        if dx.abs() >= SWIPE_DISTANCE
+            && dx.abs() >= dy.abs() * SWIPE_SLOPE
+            && dx.abs() / duration >= SWIPE_VELOCITY
+        {
+            // The slide follows the finger, so swiping left brings the next one
+            return Some(if dx < 0.0 {
+                Gesture::Forward
+            } else {
+                Gesture::Back
+            });
+        }
+
+        if dx.hypot(dy) <= TAP_DISTANCE && duration <= TAP_DURATION && !interactive {
+            if self.x <= width * EDGE_ZONE {
+                return Some(Gesture::Back);
+            }
+            if self.x >= width * (1.0 - EDGE_ZONE) {
+                return Some(Gesture::Forward);
+            }
+        }
+        None
+    }
+}
+
+fn is_interactive(event: &PointerEvent) -> bool {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<Element>().ok())
+        .and_then(|element| element.closest(INTERACTIVE).ok().flatten())
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f64 = 1000.0;
+
+    fn start(x: f64, y: f64) -> Start {
+        Start {
+            id: 1,
+            x,
+            y,
+            time: 1000.0,
+        }
+    }
+
+    // Lift the pointer `dx`, `dy` away from where it went down after `duration` ms
+    fn lift(start: Start, dx: f64, dy: f64, duration: f64) -> Option<Gesture> {
+        start.classify(
+            start.x + dx,
+            start.y + dy,
+            start.time + duration,
+            WIDTH,
+            false,
+        )
+    }
+
+    #[test]
+    fn swipes_step_the_way_the_slide_is_pulled() {
+        assert_eq!(
+            lift(start(500.0, 300.0), -80.0, 10.0, 100.0),
+            Some(Gesture::Forward)
+        );
+        assert_eq!(
+            lift(start(500.0, 300.0), 80.0, -10.0, 100.0),
+            Some(Gesture::Back)
+        );
+    }
+
+    #[test]
+    fn swipes_have_to_travel_far_enough() {
+        assert_eq!(lift(start(500.0, 300.0), -49.0, 0.0, 50.0), None);
+        assert_eq!(
+            lift(start(500.0, 300.0), -50.0, 0.0, 50.0),
+            Some(Gesture::Forward)
+        );
+    }
+
+    #[test]
+    fn slow_drags_are_not_swipes() {
+        // 0.3 pixels per millisecond at the least
+        assert_eq!(lift(start(500.0, 300.0), -90.0, 0.0, 301.0), None);
+        assert_eq!(
+            lift(start(500.0, 300.0), -90.0, 0.0, 300.0),
+            Some(Gesture::Forward)
+        );
+    }
+
+    #[test]
+    fn scrolling_is_not_a_swipe() {
+        assert_eq!(lift(start(500.0, 300.0), -80.0, 41.0, 100.0), None);
+        assert_eq!(
+            lift(start(500.0, 300.0), -80.0, 40.0, 100.0),
+            Some(Gesture::Forward)
+        );
+    }
+
+    #[test]
+    fn taps_near_the_edges_step() {
+        assert_eq!(
+            lift(start(100.0, 300.0), 3.0, 3.0, 100.0),
+            Some(Gesture::Back)
+        );
+        assert_eq!(
+            lift(start(900.0, 300.0), -3.0, 3.0, 100.0),
+            Some(Gesture::Forward)
+        );
+        assert_eq!(lift(start(500.0, 300.0), 0.0, 0.0, 100.0), None);
+    }
+
+    #[test]
+    fn long_presses_wandering_taps_and_controls_do_nothing() {
+        assert_eq!(lift(start(100.0, 300.0), 0.0, 0.0, 301.0), None);
+        assert_eq!(lift(start(100.0, 300.0), 8.0, 8.0, 100.0), None);
+        let tap = start(100.0, 300.0);
+        assert_eq!(
+            tap.classify(100.0, 300.0, tap.time + 50.0, WIDTH, true),
+            None
+        );
+    }
+}
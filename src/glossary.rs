@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Deserialize;
+use yew::{
+    format::{Json, Nothing},
+    services::{
+        fetch::{FetchTask, Request, Response},
+        FetchService,
+    },
+    Callback,
+};
+
+pub const GLOSSARY_URL: &str = "/api/glossary.json";
+
+/// Terms and their short definitions, loaded from `/api/glossary.json`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Glossary {
+    terms: BTreeMap<String, String>,
+}
+
+/// A glossary term found in a piece of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermMatch<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub term: &'a str,
+    pub definition: &'a str,
+}
+
+impl Glossary {
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.terms.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Find the earliest term in `text` which isn't part of `seen`.
+    /// Matching ignores ASCII case and only hits whole words.
+    pub fn find_next(&self, text: &str, seen: &HashSet<String>) -> Option<TermMatch<'_>> {
+        let haystack = text.to_ascii_lowercase();
+        self.terms
+            .iter()
+            .filter(|(term, _)| !seen.contains(term.as_str()))
+            .filter_map(|(term, definition)| {
+                let start = find_word(&haystack, &term.to_ascii_lowercase())?;
+                Some(TermMatch {
+                    start,
+                    end: start + term.len(),
+                    term,
+                    definition,
+                })
+            })
+            // Earliest match wins, prefer the longer term when they start at the same place
+            .min_by_key(|m| (m.start, usize::MAX - m.end))
+    }
+
+    /// Every term used somewhere in `text`
+    pub fn terms_in(&self, text: &str) -> Vec<&str> {
+        let haystack = text.to_ascii_lowercase();
+        self.terms
+            .keys()
+            .filter(|term| find_word(&haystack, &term.to_ascii_lowercase()).is_some())
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Id usable in the DOM for the tooltip of a term
+pub fn term_id(term: &str) -> String {
+    let slug: String = term
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("glossary-{}", slug)
+}
+
+fn find_word(haystack: &str, needle: &str) -> Option<usize> {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut from = 0;
+    while let Some(pos) = haystack[from..].find(needle) {
+        let start = from + pos;
+        let end = start + needle.len();
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[end..].chars().next();
+        // Only require a word boundary where the term itself starts or ends with a word character
+        let bounded_start = is_word(needle.chars().next());
+        let bounded_end = is_word(needle.chars().next_back());
+        if (!bounded_start || !is_word(before)) && (!bounded_end || !is_word(after)) {
+            return Some(start);
+        }
+        from = start + haystack[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+pub fn fetch(callback: Callback<Result<Glossary, anyhow::Error>>) -> FetchTask {
+    let request = Request::get(GLOSSARY_URL)
+        .body(Nothing)
+        .expect("Could not build request.");
+    let callback = Callback::from(
+        move |response: Response<Json<Result<Glossary, anyhow::Error>>>| {
+            let Json(body) = response.into_body();
+            callback.emit(body)
+        },
+    );
+    FetchService::fetch(request, callback).expect("failed to start request")
+}
@@ -0,0 +1,352 @@
+/// Renders hand-written HTML slides.
+///
+/// The HTML is parsed by the browser into an inert document, sanitised while walking it,
+/// and turned into VNodes so Yew owns the result like any other part of the page.
+/// Rust code blocks are handed to the CodeBlock component, which highlights them, and the
+/// text gets the citations and glossary tooltips of a markdown slide.
+use std::collections::HashSet;
+
+use ankan_common::{citation, markup::is_safe_url, text};
+use wasm_bindgen::JsCast;
+use web_sys::{DomParser, Element, Node, SupportedType};
+use yew::{
+    html,
+    virtual_dom::{VNode, VTag, VText},
+    Html,
+};
+
+use crate::{
+    components::code_block::CodeBlock,
+    markdown::{self, RenderContext},
+};
+
+// Elements removed together with everything inside them
+const DROPPED_TAGS: &[&str] = &[
+    "script", "style", "iframe", "frame", "object", "embed", "link", "meta", "base", "form",
+    "input", "button", "select", "textarea", "template", "noscript",
+];
+
+// Elements we keep, anything not listed here is replaced by its children
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "alt", "class", "colspan", "height", "href", "id", "rel", "rowspan", "src", "start", "target",
+    "title", "width",
+];
+
+// Inline styles may only set these, so a slide can't cover the page or load anything
+const ALLOWED_STYLE_PROPERTIES: &[&str] = &[
+    "background-color",
+    "border",
+    "border-radius",
+    "color",
+    "font-family",
+    "font-size",
+    "font-style",
+    "font-weight",
+    "height",
+    "line-height",
+    "margin",
+    "margin-bottom",
+    "margin-left",
+    "margin-right",
+    "margin-top",
+    "max-height",
+    "max-width",
+    "padding",
+    "text-align",
+    "text-decoration",
+    "vertical-align",
+    "width",
+];
+
+pub fn generate_html(source: &str, context: &RenderContext) -> Html {
+    let document = DomParser::new()
+        .and_then(|parser| parser.parse_from_string(source, SupportedType::TextHtml))
+        .expect("the browser can parse HTML");
+    let mut converter = Converter {
+        context,
+        seen_terms: HashSet::new(),
+        in_code: false,
+        in_pre: false,
+    };
+    let children = match document.body() {
+        Some(body) => converter.convert_children(&body),
+        None => vec![],
+    };
+
+    html! {
+        <div class="html-slide"> { for children.into_iter() } </div>
+    }
+}
+
+// Walks the parsed slide, giving its text the same citations and glossary terms as a
+// markdown slide
+struct Converter<'a> {
+    context: &'a RenderContext,
+    // Only the first occurrence of a glossary term on a slide gets a tooltip
+    seen_terms: HashSet<String>,
+    // Code cites nothing, and preformatted text is left exactly as written
+    in_code: bool,
+    in_pre: bool,
+}
+
+impl Converter<'_> {
+    fn convert_children(&mut self, parent: &Node) -> Vec<VNode> {
+        let nodes = parent.child_nodes();
+        (0..nodes.length())
+            .filter_map(|i| nodes.item(i))
+            .flat_map(|node| self.convert(&node))
+            .collect()
+    }
+
+    fn convert(&mut self, node: &Node) -> Vec<VNode> {
+        match node.node_type() {
+            Node::TEXT_NODE => self.convert_text(&node.node_value().unwrap_or_default()),
+            Node::ELEMENT_NODE => {
+                let element: &Element = node.unchecked_ref();
+                let tag = element.local_name();
+                if DROPPED_TAGS.contains(&tag.as_str()) {
+                    vec![]
+                } else if !ALLOWED_TAGS.contains(&tag.as_str()) {
+                    self.convert_children(node)
+                } else if tag == "pre" {
+                    vec![self.convert_pre(element)]
+                } else {
+                    vec![self.convert_element(element, &tag).into()]
+                }
+            }
+            // Comments, processing instructions and the like
+            _ => vec![],
+        }
+    }
+
+    // `[@key]` becomes a citation and glossary terms get their tooltip, like in markdown
+    fn convert_text(&mut self, text: &str) -> Vec<VNode> {
+        if self.in_pre {
+            return vec![VText::new(text.to_string()).into()];
+        }
+        let citations = if self.in_code {
+            vec![]
+        } else {
+            citation::in_text(text)
+        };
+        let glossary = &self.context.glossary;
+        let mut nodes = vec![];
+        let mut from = 0;
+        for cited in citations {
+            nodes.extend(markdown::annotate_terms(
+                &text[from..cited.start],
+                glossary,
+                &mut self.seen_terms,
+            ));
+            nodes.push(markdown::view_citation(cited.key, self.context).into());
+            from = cited.end;
+        }
+        nodes.extend(markdown::annotate_terms(
+            &text[from..],
+            glossary,
+            &mut self.seen_terms,
+        ));
+        nodes
+    }
+
+    fn convert_element(&mut self, element: &Element, tag: &str) -> VTag {
+        let mut el = VTag::new(tag.to_string());
+        for name in element
+            .get_attribute_names()
+            .iter()
+            .filter_map(|n| n.as_string())
+        {
+            let value = element.get_attribute(&name).unwrap_or_default();
+            if name.eq_ignore_ascii_case("style") {
+                let style = safe_style(&value);
+                if !style.is_empty() {
+                    el.add_attribute("style", &style);
+                }
+            } else if is_allowed_attribute(&name, &value) {
+                el.add_attribute(&name, &value);
+            }
+        }
+        // Headings get an id made from their text, for search results to scroll to
+        let is_heading = matches!(tag, "h1" | "h2" | "h3" | "h4" | "h5" | "h6");
+        if is_heading && !element.has_attribute("id") {
+            let id = text::slugify(&element.text_content().unwrap_or_default());
+            el.add_attribute("id", &id);
+        }
+        // Don't hand the slide's window to sites opened from it
+        if element.get_attribute("target").as_deref() == Some("_blank") {
+            el.add_attribute("rel", &"noopener noreferrer");
+        }
+        let (in_code, in_pre) = (self.in_code, self.in_pre);
+        self.in_code |= tag == "code";
+        self.in_pre |= tag == "pre";
+        el.add_children(self.convert_children(element));
+        self.in_code = in_code;
+        self.in_pre = in_pre;
+        el
+    }
+
+    // `<pre><code class="language-rust">` is rendered by the CodeBlock component, with
+    // highlighting and the toolbar, any other preformatted block is kept as is.
+    fn convert_pre(&mut self, pre: &Element) -> VNode {
+        let code = pre.query_selector("code.language-rust").ok().flatten();
+        match code {
+            Some(code) => {
+                let source = dedent(&code.text_content().unwrap_or_default());
+                html! { <CodeBlock code=source language=Some("rust".to_string()) /> }
+            }
+            None => self.convert_element(pre, "pre").into(),
+        }
+    }
+}
+
+fn is_allowed_attribute(name: &str, value: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let known = ALLOWED_ATTRIBUTES.contains(&name.as_str())
+        || name.starts_with("aria-")
+        || name.starts_with("data-");
+    known && (!matches!(name.as_str(), "href" | "src") || is_safe_url(value))
+}
+
+// The declarations of an inline style setting allowed properties, without anything
+// that could fetch a resource or run script
+fn safe_style(style: &str) -> String {
+    style
+        .split(';')
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            let property = property.trim().to_ascii_lowercase();
+            let value = value.trim();
+            let lowercase = value.to_ascii_lowercase();
+            let is_safe = ALLOWED_STYLE_PROPERTIES.contains(&property.as_str())
+                && !value.is_empty()
+                && !value.contains(['\\', '"', '\'', '<', '>'])
+                && !lowercase.contains("url(")
+                && !lowercase.contains("expression(");
+            Some(format!("{}: {}", property, value)).filter(|_| is_safe)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Hand-written slides indent their code along with the markup, strip that indentation
+/// and the blank lines around the code.
+fn dedent(code: &str) -> String {
+    let lines: Vec<&str> = code.lines().collect();
+    let first = lines.iter().position(|l| !l.trim().is_empty());
+    let last = lines.iter().rposition(|l| !l.trim().is_empty());
+    let lines = match (first, last) {
+        (Some(first), Some(last)) => &lines[first..=last],
+        _ => return String::new(),
+    };
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut result = String::new();
+    for line in lines {
+        result.push_str(line.get(indent..).unwrap_or_else(|| line.trim_start()));
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_allowed_style_properties() {
+        assert_eq!(
+            safe_style("color: red; FONT-SIZE: 2em;; margin:0"),
+            "color: red; font-size: 2em; margin: 0"
+        );
+    }
+
+    #[test]
+    fn drops_styles_that_load_or_run_anything() {
+        assert_eq!(safe_style("background-image: url(x.png)"), "");
+        assert_eq!(safe_style("width: expression(alert(1))"), "");
+        assert_eq!(
+            safe_style("width: EXPRESSION(alert(1)); color: blue"),
+            "color: blue"
+        );
+        assert_eq!(safe_style("color: URL(javascript:alert(1))"), "");
+        assert_eq!(safe_style("color: \"red\"; width: 1\\0em"), "");
+        assert_eq!(safe_style("position: fixed; color: red"), "color: red");
+    }
+
+    #[test]
+    fn drops_event_handlers_and_unknown_attributes() {
+        assert!(!is_allowed_attribute("onclick", "alert(1)"));
+        assert!(!is_allowed_attribute("OnMouseOver", "alert(1)"));
+        assert!(!is_allowed_attribute("formaction", "/"));
+        assert!(is_allowed_attribute("CLASS", "title"));
+        assert!(is_allowed_attribute("aria-label", "Ferris"));
+        assert!(is_allowed_attribute("data-line", "3"));
+    }
+
+    #[test]
+    fn drops_links_with_unsafe_schemes() {
+        assert!(!is_allowed_attribute("href", "javascript:alert(1)"));
+        assert!(!is_allowed_attribute("HREF", "JavaScript:alert(1)"));
+        assert!(!is_allowed_attribute(
+            "src",
+            "data:image/svg+xml,<svg onload=alert(1)>"
+        ));
+        assert!(is_allowed_attribute("href", "https://www.rust-lang.org"));
+        assert!(is_allowed_attribute("src", "/images/ferris.svg"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+use yew::{
+    format::{Json, Nothing},
+    services::{
+        fetch::{FetchTask, Request, Response},
+        FetchService,
+    },
+    Callback,
+};
+
+/// Overrides of the default bindings, e.g. `{"l": "next", "Space": "none"}`.
+/// Deploying it is optional.
+pub const KEYMAP_URL: &str = "/api/keymap.json";
+
+// Enter and Space press these when focused
+const CONTROLS: &str = "a, button, summary, [role=button]";
+
+/// What a key does while presenting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Next,
+    Previous,
+    /// Past any fragments left on the slide
+    NextSlide,
+    PreviousSlide,
+    /// Between the sub-slides stacked beneath a slide
+    Down,
+    Up,
+    NextSection,
+    PreviousSection,
+    First,
+    Last,
+    /// Go to the slide whose number was typed
+    Jump,
+    /// Open the go-to dialog
+    GoTo,
+    /// Show the keyboard shortcuts
+    Help,
+    /// Close whatever dialog is open, or else toggle the overview
+    Close,
+    /// Show every slide of the deck in a grid
+    Overview,
+    /// Between the rows of the overview, elsewhere the keys are left to scroll the slide
+    RowUp,
+    RowDown,
+    /// Unbinds the key when used in an override
+    None,
+}
+
+impl Action {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Action::Next => "Next fragment or slide",
+            Action::Previous => "Previous fragment or slide",
+            Action::NextSlide => "Next slide, past the fragments left",
+            Action::PreviousSlide => "Previous slide",
+            Action::Down => "Sub-slide beneath",
+            Action::Up => "Sub-slide above",
+            Action::NextSection => "Next section",
+            Action::PreviousSection => "Start of the section, or of the one before",
+            Action::First => "First slide",
+            Action::Last => "Last slide",
+            Action::Jump => "Go to the slide number typed before it",
+            Action::GoTo => "Go to a slide by number or title",
+            Action::Help => "Show these shortcuts",
+            Action::Close => "Close the dialog, or show all slides",
+            Action::Overview => "Show all slides",
+            Action::RowUp => "Row above, in the overview",
+            Action::RowDown => "Row below, in the overview",
+            Action::None => "Nothing",
+        }
+    }
+}
+
+/// Keys and the actions they're bound to.
+/// Keys are named after `KeyboardEvent.key`, with a space being `Space`, and named keys
+/// may be prefixed with `Shift+` or `Alt+`, as in `Shift+Space`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: BTreeMap<String, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            ("ArrowRight", Action::Next),
+            ("PageDown", Action::Next),
+            ("Space", Action::Next),
+            ("ArrowLeft", Action::Previous),
+            ("PageUp", Action::Previous),
+            ("Shift+Space", Action::Previous),
+            ("Shift+ArrowRight", Action::NextSlide),
+            ("Shift+ArrowLeft", Action::PreviousSlide),
+            // The arrows alone scroll a slide too long for the screen
+            ("Alt+ArrowDown", Action::Down),
+            ("Alt+ArrowUp", Action::Up),
+            ("]", Action::NextSection),
+            ("[", Action::PreviousSection),
+            ("Home", Action::First),
+            ("End", Action::Last),
+            ("Enter", Action::Jump),
+            ("g", Action::GoTo),
+            ("?", Action::Help),
+            ("Escape", Action::Close),
+            ("o", Action::Overview),
+            ("ArrowUp", Action::RowUp),
+            ("ArrowDown", Action::RowDown),
+        ];
+        Keymap {
+            bindings: bindings
+                .iter()
+                .map(|(key, action)| (key.to_string(), *action))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The default bindings with the overrides applied
+    pub fn with_overrides(overrides: BTreeMap<String, Action>) -> Self {
+        let mut keymap = Keymap::default();
+        for (key, action) in overrides {
+            if action == Action::None {
+                keymap.bindings.remove(&key);
+            } else {
+                keymap.bindings.insert(key, action);
+            }
+        }
+        keymap
+    }
+
+    /// The action for a key press, if the page should handle it at all
+    pub fn action(&self, event: &KeyboardEvent) -> Option<Action> {
+        if is_ignored(event) {
+            return None;
+        }
+        self.bindings.get(&key_name(event)).copied()
+    }
+
+    /// Every action with the keys bound to it
+    pub fn by_action(&self) -> BTreeMap<Action, Vec<&str>> {
+        let mut actions: BTreeMap<Action, Vec<&str>> = BTreeMap::new();
+        for (key, action) in &self.bindings {
+            actions.entry(*action).or_default().push(key);
+        }
+        actions
+    }
+}
+
+/// The digit typed, when a digit is typed outside of any input
+pub fn digit(event: &KeyboardEvent) -> Option<u32> {
+    if is_ignored(event) {
+        return None;
+    }
+    let key = event.key();
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c.to_digit(10),
+        _ => None,
+    }
+}
+
+/// Keys are left to the browser while typing or holding Ctrl or Meta, and so are Enter
+/// and Space when they press the focused link or button
+pub fn is_ignored(event: &KeyboardEvent) -> bool {
+    if event.ctrl_key() || event.meta_key() {
+        return true;
+    }
+    let target = event.target().and_then(|t| t.dyn_into::<Element>().ok());
+    match target {
+        Some(element) => {
+            let presses = matches!(event.key().as_str(), "Enter" | " ")
+                && element.closest(CONTROLS).ok().flatten().is_some();
+            presses
+                || matches!(
+                    element.local_name().as_str(),
+                    "input" | "textarea" | "select"
+                )
+                || element
+                    .dyn_ref::<HtmlElement>()
+                    .is_some_and(|e| e.is_content_editable())
+        }
+        None => false,
+    }
+}
+
+fn key_name(event: &KeyboardEvent) -> String {
+    let key = match event.key().as_str() {
+        " " => "Space".to_string(),
+        key => key.to_string(),
+    };
+    // Shift is part of the character typed, '?' rather than 'Shift+/'
+    if key.chars().count() == 1 {
+        return key;
+    }
+    let mut name = String::new();
+    if event.alt_key() {
+        name.push_str("Alt+");
+    }
+    if event.shift_key() {
+        name.push_str("Shift+");
+    }
+    name.push_str(&key);
+    name
+}
+
+/// Fetch the deployed overrides, the default keymap is used when there are none
+pub fn fetch(callback: Callback<Keymap>) -> FetchTask {
+    let request = Request::get(KEYMAP_URL)
+        .body(Nothing)
+        .expect("Could not build request.");
+    let callback = Callback::from(
+        move |response: Response<Json<Result<BTreeMap<String, Action>, anyhow::Error>>>| {
+            let Json(body) = response.into_body();
+            callback.emit(Keymap::with_overrides(body.unwrap_or_default()))
+        },
+    );
+    FetchService::fetch(request, callback).expect("failed to start request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keymap: &Keymap, action: Action) -> Vec<&str> {
+        keymap.by_action().remove(&action).unwrap_or_default()
+    }
+
+    #[test]
+    fn overrides_add_and_rebind_keys() {
+        let overrides: BTreeMap<String, Action> =
+            serde_json::from_str(r#"{"l": "next", "h": "previous", "ArrowRight": "go-to"}"#)
+                .unwrap();
+        let keymap = Keymap::with_overrides(overrides);
+        assert_eq!(keys(&keymap, Action::Next), vec!["PageDown", "Space", "l"]);
+        assert_eq!(
+            keys(&keymap, Action::Previous),
+            vec!["ArrowLeft", "PageUp", "Shift+Space", "h"]
+        );
+        assert_eq!(keys(&keymap, Action::GoTo), vec!["ArrowRight", "g"]);
+    }
+
+    #[test]
+    fn none_unbinds_a_key() {
+        let overrides: BTreeMap<String, Action> =
+            serde_json::from_str(r#"{"Space": "none", "o": "none"}"#).unwrap();
+        let keymap = Keymap::with_overrides(overrides);
+        assert_eq!(keys(&keymap, Action::Next), vec!["ArrowRight", "PageDown"]);
+        assert!(keys(&keymap, Action::Overview).is_empty());
+        assert!(keys(&keymap, Action::None).is_empty());
+    }
+
+    #[test]
+    fn no_overrides_is_the_default() {
+        assert_eq!(Keymap::with_overrides(BTreeMap::new()), Keymap::default());
+    }
+
+    #[test]
+    fn unknown_actions_are_rejected() {
+        let overrides = serde_json::from_str::<BTreeMap<String, Action>>(r#"{"x": "explode"}"#);
+        assert!(overrides.is_err());
+    }
+}
@@ -0,0 +1,124 @@
+/// A small least recently used cache, behind the slide and render caches.
+///
+/// Caches hold a few dozen entries at most, so a scan of a deque beats hashing.
+use std::{borrow::Borrow, collections::VecDeque};
+
+#[derive(Debug)]
+pub struct Lru<K, V> {
+    // Most recently used entry last
+    entries: VecDeque<(K, V)>,
+    capacity: usize,
+}
+
+impl<K: PartialEq, V> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Lru {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// The value for the key, which becomes the most recently used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        let pos = self.entries.iter().position(|(k, _)| k.borrow() == key)?;
+        let entry = self.entries.remove(pos)?;
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, value)| value)
+    }
+
+    /// Whether the key is cached, without counting as a use
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        self.entries.iter().any(|(k, _)| k.borrow() == key)
+    }
+
+    /// Cache the value as the most recently used, replacing any for the same key.
+    /// The least recently used entry makes room when the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.retain(|(k, _)| *k != key);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(keys: &[&'static str]) -> Lru<&'static str, usize> {
+        let mut lru = Lru::new(keys.len());
+        for (value, key) in keys.iter().enumerate() {
+            lru.insert(*key, value);
+        }
+        lru
+    }
+
+    #[test]
+    fn evicts_the_oldest_entry_when_full() {
+        let mut lru = filled(&["a", "b", "c"]);
+        lru.insert("d", 3);
+        assert!(!lru.contains("a"));
+        assert!(lru.contains("b"));
+        assert_eq!(lru.get("d"), Some(&3));
+    }
+
+    #[test]
+    fn get_makes_an_entry_the_most_recent() {
+        let mut lru = filled(&["a", "b", "c"]);
+        assert_eq!(lru.get("a"), Some(&0));
+        lru.insert("d", 3);
+        assert!(lru.contains("a"));
+        assert!(!lru.contains("b"));
+    }
+
+    #[test]
+    fn contains_does_not_count_as_a_use() {
+        let mut lru = filled(&["a", "b", "c"]);
+        assert!(lru.contains("a"));
+        lru.insert("d", 3);
+        assert!(!lru.contains("a"));
+    }
+
+    #[test]
+    fn insert_replaces_the_same_key_without_evicting() {
+        let mut lru = filled(&["a", "b", "c"]);
+        lru.insert("a", 10);
+        assert_eq!(lru.get("a"), Some(&10));
+        assert!(lru.contains("b"));
+        assert!(lru.contains("c"));
+        // "a" was refreshed by the insert, so "b" goes first
+        lru.insert("d", 3);
+        assert!(!lru.contains("b"));
+        assert!(lru.contains("a"));
+    }
+
+    #[test]
+    fn looks_up_owned_keys_by_their_borrowed_form() {
+        let mut lru: Lru<String, usize> = Lru::new(2);
+        lru.insert("slide.md".to_string(), 1);
+        assert!(lru.contains("slide.md"));
+        assert_eq!(lru.get("slide.md"), Some(&1));
+        assert_eq!(lru.get("other.md"), None);
+    }
+
+    #[test]
+    fn clear_drops_everything() {
+        let mut lru = filled(&["a", "b"]);
+        lru.clear();
+        assert!(!lru.contains("a"));
+        assert!(!lru.contains("b"));
+    }
+}
@@ -1,10 +1,23 @@
 #![recursion_limit = "1024"]
 mod app;
+mod bibliography;
 mod components;
+mod deck_cursor;
+mod deck_store;
+mod export;
+mod gesture;
+mod glossary;
+mod html_slide;
+mod keymap;
+mod lru;
 mod markdown;
 mod pages;
+mod render_cache;
+mod search;
+mod slide_cache;
 mod slides_data;
 mod switch;
+mod zip;
 
 #[cfg(not(debug_assertions))]
 #[global_allocator]
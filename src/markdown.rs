@@ -7,14 +7,44 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use yew::{
     html,
-    services::ConsoleService,
     virtual_dom::{VNode, VTag, VText},
     Html,
 };
 
-pub fn generate_html(markdown: &str) -> Html {
-    let mut res_tree = vec![];
-    let mut tree = vec![];
+use std::collections::HashSet;
+
+use ankan_common::{citation, markup, text};
+
+use crate::{
+    bibliography::{self, Bibliography, Citations},
+    components::code_block::CodeBlock,
+    glossary::{self, Glossary},
+    switch::AppRoute,
+};
+
+/// Deck-level data the slides are rendered with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderContext {
+    /// Id of the deck the slide belongs to
+    pub deck: String,
+    pub glossary: Glossary,
+    pub bibliography: Bibliography,
+    pub citations: Citations,
+}
+
+pub fn generate_html(markdown: &str, context: &RenderContext) -> Html {
+    let mut res_tree: Vec<VNode> = vec![];
+    let mut tree: Vec<VTag> = vec![];
+    // Code blocks are buffered as a whole so they can be handed to the CodeBlock component
+    let mut code_block: Option<(FenceInfo, String)> = None;
+    // A main.rs can be downloaded as a cargo project if the slide also holds its Cargo.toml
+    let cargo_toml = find_code_block(markdown, "Cargo.toml");
+    // Only the first occurrence of a glossary term on a slide gets a tooltip
+    let mut seen_terms = HashSet::new();
+    // The text of a citation, '@key', is replaced by its number
+    let mut in_citation = false;
+    // Headings get an id made from their text, for search results to scroll to
+    let mut heading_text: Option<String> = None;
 
     // Instead of repeating this code or exposing it as a mmethod taking the 'tree'
     // this is a simple way of generating the code we need in its stead.
@@ -26,40 +56,88 @@ pub fn generate_html(markdown: &str) -> Html {
         }};
     }
 
-    for event in Parser::new_ext(&markdown, Options::empty()) {
+    let mut resolve_citation = |link: pulldown_cmark::BrokenLink| {
+        citation::resolve(link.reference).map(|(url, title)| (url.into(), title.into()))
+    };
+    let parser = Parser::new_with_broken_link_callback(
+        markdown,
+        Options::empty(),
+        Some(&mut resolve_citation),
+    );
+
+    for event in parser {
         match event {
-            Event::Start(t) => tree.push(to_vtag(t)),
+            Event::Start(Tag::Link(_, ref dest, _)) if dest.starts_with(citation::SCHEME) => {
+                let key = &dest[citation::SCHEME.len()..];
+                tree.push(view_citation(key, context));
+                in_citation = true;
+            }
+            Event::End(Tag::Link(..)) if in_citation => {
+                in_citation = false;
+                let top = tree.pop().unwrap();
+                add_child!(top.into());
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                code_block = Some((FenceInfo::from(&kind), String::new()));
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                let (info, code) = code_block.take().unwrap_or_default();
+                let cargo_toml = match info.file_name.as_deref() {
+                    Some("main.rs") => cargo_toml.clone(),
+                    _ => None,
+                };
+                let block = html! {
+                    <CodeBlock
+                        code=code
+                        language=info.language
+                        file_name=info.file_name
+                        cargo_toml=cargo_toml
+                    />
+                };
+                if tree.is_empty() {
+                    res_tree.push(block);
+                } else {
+                    add_child!(block);
+                }
+            }
+            Event::Start(t) => {
+                if let Tag::Heading(_) = t {
+                    heading_text = Some(String::new());
+                }
+                tree.push(to_vtag(t))
+            }
             Event::End(t) => {
                 let len = tree.len();
                 assert!(len >= 1);
                 let mut top = tree.pop().unwrap();
-                if let Tag::CodeBlock(_) = t {
-                    let mut pre = VTag::new("div");
-                    // pre.add_attribute("class", &"container");
-                    pre.add_child(top.into());
-                    top = pre;
+                if let (Tag::Heading(_), Some(text)) = (t, heading_text.take()) {
+                    top.add_attribute("id", &text::slugify(&text));
                 }
                 if len == 1 {
-                    res_tree.push(top);
+                    res_tree.push(top.into());
                 } else {
                     tree[len - 2].add_child(top.into());
                 }
             }
             Event::Code(c) => {
-                ConsoleService::log(&format!("Text: {}", &c.into_string()));
+                if let Some(heading) = heading_text.as_mut() {
+                    heading.push_str(&c);
+                }
+                let mut code = VTag::new("code");
+                code.add_children(annotate_terms(&c, &context.glossary, &mut seen_terms));
+                add_child!(code.into());
             }
+            Event::Text(_) if in_citation => {}
             Event::Text(text) => {
-                // We've added the 'Code' tag in the to_tag method
-                let text = text.into_string();
-
-                if tree[tree.len() - 1].tag() == "pre" {
-                    let colorized = colorize(&text);
-                    let span = yew::utils::document().create_element("code").unwrap();
-
-                    span.set_inner_html(&colorized);
-                    add_child!(VNode::VRef(span.into()).into());
+                if let Some(heading) = heading_text.as_mut() {
+                    heading.push_str(&text);
+                }
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
                 } else {
-                    add_child!(VText::new(text).into());
+                    for node in annotate_terms(&text, &context.glossary, &mut seen_terms) {
+                        add_child!(node);
+                    }
                 }
             }
             Event::SoftBreak => {
@@ -76,7 +154,7 @@ pub fn generate_html(markdown: &str) -> Html {
     }
 
     if res_tree.len() == 1 {
-        VNode::VTag(Box::new(res_tree.pop().unwrap()))
+        res_tree.pop().unwrap()
     } else {
         html! {
             <div> { for res_tree.into_iter() } </div>
@@ -84,6 +162,105 @@ pub fn generate_html(markdown: &str) -> Html {
     }
 }
 
+/// Superscript number linking to the reference on the References slide
+pub fn view_citation(key: &str, context: &RenderContext) -> VTag {
+    let mut sup = VTag::new("sup");
+    sup.add_attribute("class", &"citation");
+
+    let mut link = VTag::new("a");
+    let references =
+        AppRoute::DeckSlidesName(context.deck.clone(), markup::REFERENCES_SLUG.to_string())
+            .into_route();
+    link.add_attribute(
+        "href",
+        &format!("{}#{}", references, bibliography::reference_id(key)),
+    );
+    if let Some(reference) = context.bibliography.get(key) {
+        link.add_attribute("title", &reference.describe());
+    }
+    let number = context
+        .citations
+        .number(key)
+        .map_or_else(|| "?".to_string(), |n| n.to_string());
+    link.add_child(VText::new(format!("[{}]", number)).into());
+
+    sup.add_child(link.into());
+    sup
+}
+
+/// Split text into plain text and tooltips for the glossary terms not yet seen on the slide
+pub fn annotate_terms(text: &str, glossary: &Glossary, seen: &mut HashSet<String>) -> Vec<VNode> {
+    let mut nodes = vec![];
+    let mut rest = text;
+    while let Some(m) = glossary.find_next(rest, seen) {
+        if m.start > 0 {
+            nodes.push(VText::new(rest[..m.start].to_string()).into());
+        }
+        let id = glossary::term_id(m.term);
+        nodes.push(html! {
+            <span class="glossary-term" tabindex="0" aria-describedby=id.clone()>
+                { &rest[m.start..m.end] }
+                <span class="glossary-term__tooltip" role="tooltip" id=id>
+                    { m.definition }
+                </span>
+            </span>
+        });
+        seen.insert(m.term.to_string());
+        rest = &rest[m.end..];
+    }
+    if !rest.is_empty() {
+        nodes.push(VText::new(rest.to_string()).into());
+    }
+    nodes
+}
+
+/// What we care about from the info string of a fenced code block.
+/// The first word is the language, any word with a dot in it names the file,
+/// e.g. ```` ```rust,main.rs ```` or ```` ```toml Cargo.toml ````.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FenceInfo {
+    pub language: Option<String>,
+    pub file_name: Option<String>,
+}
+
+impl From<&CodeBlockKind<'_>> for FenceInfo {
+    fn from(kind: &CodeBlockKind) -> Self {
+        let info = match kind {
+            CodeBlockKind::Fenced(info) => info.as_ref(),
+            CodeBlockKind::Indented => "",
+        };
+        let mut words = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|w| !w.is_empty());
+        FenceInfo {
+            language: words.next().map(str::to_string),
+            file_name: words.find(|w| w.contains('.')).map(str::to_string),
+        }
+    }
+}
+
+// Find the content of the code block for the given file name, if any
+fn find_code_block(markdown: &str, file_name: &str) -> Option<String> {
+    let mut found: Option<String> = None;
+    for event in Parser::new_ext(markdown, Options::empty()) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind))
+                if FenceInfo::from(&kind).file_name.as_deref() == Some(file_name) =>
+            {
+                found = Some(String::new());
+            }
+            Event::Text(text) => {
+                if let Some(code) = found.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) if found.is_some() => return found,
+            _ => {}
+        }
+    }
+    None
+}
+
 // Generate a VTag element for later rendering to the actual dom
 // Use VTag over string to skip string -> Html later
 fn to_vtag(tag: Tag) -> VTag {
@@ -94,7 +271,7 @@ fn to_vtag(tag: Tag) -> VTag {
             v.add_attribute("class", &"title");
             v
         }
-        Heading(s) => {
+        Heading(_) => {
             let mut v = VTag::new("p");
             v.add_attribute("class", &"");
             v
@@ -104,16 +281,8 @@ fn to_vtag(tag: Tag) -> VTag {
             el.add_attribute("class", &"blockquote");
             el
         }
-        CodeBlock(code_type) => {
-            let mut el = VTag::new("pre");
-            if let CodeBlockKind::Fenced(lang) = code_type {
-                match lang.as_ref() {
-                    "rust" => el.add_attribute("class", &"language-rust"),
-                    _ => {}
-                };
-            }
-            el
-        }
+        // Code blocks are buffered in generate_html and rendered by the CodeBlock component
+        CodeBlock(_) => VTag::new("pre"),
         List(None) => VTag::new("ul"),
         List(Some(1)) => VTag::new("ol"),
         List(Some(ref start)) => {
@@ -165,7 +334,56 @@ fn to_vtag(tag: Tag) -> VTag {
     }
 }
 
-fn colorize(code: &str) -> String {
+/// Source of a code block split according to the rustdoc convention,
+/// where lines starting with `# ` are boilerplate hidden from the reader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSource {
+    /// What's shown on the slide
+    pub visible: String,
+    /// The complete program, used for copying and running the code
+    pub full: String,
+    pub has_hidden: bool,
+}
+
+pub fn split_hidden_lines(code: &str) -> CodeSource {
+    let mut visible = String::with_capacity(code.len());
+    let mut full = String::with_capacity(code.len());
+    let mut has_hidden = false;
+
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some(escaped) = trimmed.strip_prefix("##") {
+            // '##' escapes a line that should start with a visible '#'
+            let line = format!("{}#{}", indent, escaped);
+            visible.push_str(&line);
+            visible.push('\n');
+            full.push_str(&line);
+        } else if trimmed == "#" {
+            has_hidden = true;
+        } else if let Some(hidden) = trimmed
+            .strip_prefix("# ")
+            .or_else(|| trimmed.strip_prefix("#\t"))
+        {
+            has_hidden = true;
+            full.push_str(indent);
+            full.push_str(hidden);
+        } else {
+            visible.push_str(line);
+            visible.push('\n');
+            full.push_str(line);
+        }
+        full.push('\n');
+    }
+
+    CodeSource {
+        visible,
+        full,
+        has_hidden,
+    }
+}
+
+pub fn colorize(code: &str) -> String {
     let m = highlight(code);
     if let Some(res) = m.as_string() {
         res
@@ -178,3 +396,38 @@ fn colorize(code: &str) -> String {
 extern "C" {
     fn highlight(html: &str) -> JsValue;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_boilerplate_lines() {
+        let code = "# use std::fmt;\nfn main() {\n    # let hidden = 1;\n    println!(\"hi\");\n}";
+        let source = split_hidden_lines(code);
+        assert_eq!(source.visible, "fn main() {\n    println!(\"hi\");\n}\n");
+        assert_eq!(
+            source.full,
+            "use std::fmt;\nfn main() {\n    let hidden = 1;\n    println!(\"hi\");\n}\n"
+        );
+        assert!(source.has_hidden);
+    }
+
+    #[test]
+    fn a_lone_hash_is_a_hidden_blank_line() {
+        let source = split_hidden_lines("#\nfn main() {}");
+        assert_eq!(source.visible, "fn main() {}\n");
+        assert_eq!(source.full, "\nfn main() {}\n");
+        assert!(source.has_hidden);
+    }
+
+    #[test]
+    fn attributes_and_escaped_hashes_stay_visible() {
+        let code = "#[derive(Debug)]\n#![allow(unused)]\n## not hidden\nstruct A;";
+        let source = split_hidden_lines(code);
+        let expected = "#[derive(Debug)]\n#![allow(unused)]\n# not hidden\nstruct A;\n";
+        assert_eq!(source.visible, expected);
+        assert_eq!(source.full, expected);
+        assert!(!source.has_hidden);
+    }
+}
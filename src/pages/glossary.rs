@@ -0,0 +1,168 @@
+use std::rc::Rc;
+
+use ankan_common::markup;
+use yew::{
+    prelude::*,
+    services::{fetch::FetchTask, ConsoleService},
+};
+use yewtil::NeqAssign;
+
+use crate::{
+    glossary::{self, Glossary},
+    slides_data::{self, FetchError, Format, Manifest, SlideContent},
+    switch::{AppAnchor, AppRoute},
+};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub manifest: Rc<Manifest>,
+}
+
+pub enum Msg {
+    ReceiveGlossary(Result<Glossary, anyhow::Error>),
+    ReceiveSlide(usize, Result<SlideContent, FetchError>),
+}
+
+/// Lists every glossary term with links to the slides using it
+pub struct GlossaryModel {
+    props: Props,
+    glossary: Option<Glossary>,
+    // Why the glossary couldn't be fetched
+    error: Option<String>,
+    // Text of each slide in the manifest, in the order of Manifest::slides. HTML slides
+    // are matched on their text like their tooltips are, without tags and entities
+    slides: Vec<Option<String>>,
+    _tasks: Vec<FetchTask>,
+}
+
+impl Component for GlossaryModel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut tasks = vec![glossary::fetch(link.callback(Msg::ReceiveGlossary))];
+        // The References slide is generated and holds no terms of its own
+        let slides = props
+            .manifest
+            .slides()
+            .enumerate()
+            .filter(|(_, (_, slide))| !slide.is_references());
+        for (index, (_, slide)) in slides {
+            let callback = link.callback(move |text| Msg::ReceiveSlide(index, text));
+            tasks.push(slides_data::fetch_text(&slide.url, callback));
+        }
+
+        let slides = vec![None; props.manifest.slides().count()];
+        GlossaryModel {
+            props,
+            glossary: None,
+            error: None,
+            slides,
+            _tasks: tasks,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ReceiveGlossary(Ok(glossary)) => self.glossary = Some(glossary),
+            Msg::ReceiveSlide(index, Ok(content)) => {
+                let (_, slide) = self.props.manifest.slides().nth(index).unwrap();
+                let text = match slide.format(content.content_type.as_deref()) {
+                    Format::Html => markup::strip_tags(&content.text),
+                    _ => content.text,
+                };
+                self.slides[index] = Some(text);
+            }
+            Msg::ReceiveGlossary(Err(error)) => {
+                ConsoleService::log(&error.to_string());
+                self.error = Some(error.to_string());
+            }
+            Msg::ReceiveSlide(_, Err(error)) => {
+                ConsoleService::log(&error.to_string());
+                return false;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        let glossary = match (&self.glossary, &self.error) {
+            (Some(glossary), _) => glossary,
+            (None, Some(error)) => {
+                return html! {
+                    <div class="hero-body">
+                        <div class="container">
+                            <article class="message is-danger">
+                                <div class="message-header">
+                                    <p>{ "Could not load the glossary" }</p>
+                                </div>
+                                <div class="message-body">{ error }</div>
+                            </article>
+                        </div>
+                    </div>
+                };
+            }
+            (None, None) => return html! { <p>{ "Fetching data..." }</p> },
+        };
+
+        html! {
+            <div class="hero-body">
+                <div class="container content">
+                    <h1 class="title">{ "Glossary" }</h1>
+                    <dl>
+                        { for glossary.iter().map(|(term, definition)| self.view_term(glossary, term, definition)) }
+                    </dl>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl GlossaryModel {
+    fn view_term(&self, glossary: &Glossary, term: &str, definition: &str) -> Html {
+        let used_in = self
+            .props
+            .manifest
+            .slides()
+            .zip(self.slides.iter())
+            .filter(|(_, text)| {
+                text.as_deref()
+                    .is_some_and(|text| glossary.terms_in(text).contains(&term))
+            })
+            .map(|((deck, slide), _)| {
+                html! {
+                    <li>
+                        <AppAnchor route=AppRoute::DeckSlidesName(deck.id.clone(), slide.slug.clone())>
+                            { &slide.title }
+                        </AppAnchor>
+                    </li>
+                }
+            })
+            .collect::<Vec<Html>>();
+
+        let used_in = if used_in.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="is-size-7">
+                    { "Used in:" }
+                    <ul class="glossary__slides">{ for used_in }</ul>
+                </div>
+            }
+        };
+
+        html! {
+            <>
+                <dt id=glossary::term_id(term)>{ term }</dt>
+                <dd>
+                    <p>{ definition }</p>
+                    { used_in }
+                </dd>
+            </>
+        }
+    }
+}
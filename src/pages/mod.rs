@@ -1,3 +1,5 @@
+pub mod glossary;
 pub mod home;
 pub mod not_found;
 pub mod slides;
+pub mod tags;
@@ -26,6 +26,10 @@ impl Component for PageNotFound {
     }
 
     fn view(&self) -> Html {
+        let subtitle = match &self.props.route {
+            Some(route) => format!("Nothing was found at {}", route),
+            None => "This page does not seem to exist".to_string(),
+        };
         html! {
             <section class="hero is-danger is-bold is-large">
                 <div class="hero-body">
@@ -34,7 +38,7 @@ impl Component for PageNotFound {
                             { "Page not found" }
                         </h1>
                         <h2 class="subtitle">
-                            { "This page does not seem to exist" }
+                            { subtitle }
                         </h2>
                     </div>
                 </div>
@@ -1,20 +1,67 @@
-use web_sys::KeyboardEvent;
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
+use ankan_common::markup;
+use web_sys::{KeyboardEvent, PointerEvent};
+use yew::services::{
+    fetch::FetchTask,
+    keyboard::{KeyListenerHandle, KeyboardService},
+    timeout::TimeoutTask,
+    ConsoleService, TimeoutService,
+};
 use yew::{
-    format::Nothing,
-    services::{
-        fetch::{FetchTask, Request, Response},
-        keyboard::{KeyListenerHandle, KeyboardService},
-        ConsoleService, FetchService,
-    },
+    agent::{Dispatched, Dispatcher},
+    html,
+    virtual_dom::VNode,
+    Component, ComponentLink, Html, Properties, ShouldRender,
 };
-use yew::{html, Component, ComponentLink, Html, Properties, ShouldRender};
 use yew_router::{agent::RouteRequest, prelude::*};
 
-use crate::{markdown, slides_data::Slide, slides_data::SLIDES, switch::AppRoute};
+use crate::{
+    bibliography::{self, Bibliography, Citations},
+    components::{
+        cached_html::CachedHtml,
+        go_to::GoTo,
+        overview::{Overview, Thumbnail},
+        progress_bar::ProgressBar,
+        shortcuts::Shortcuts,
+        slide_controls::SlideControls,
+    },
+    deck_cursor::{Bounds, CursorPosition, DeckCursor, SlideLayout},
+    deck_store::{DeckStore, FetchState, Position, Request},
+    gesture::{self, Gesture},
+    glossary::{self, Glossary},
+    html_slide,
+    keymap::{self, Action, Keymap},
+    markdown::{self, RenderContext},
+    render_cache::RenderCache,
+    slide_cache::{self, SlideCache},
+    slides_data::{self, Deck, FetchError, Format, Manifest, Slide, SlideContent, Transition},
+    switch::{AppAnchor, AppRoute},
+};
+
+// How often a transient failure to fetch the slide is retried, waiting twice as
+// long each time
+const MAX_RETRIES: u32 = 3;
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+// How long a slide may take to load before a skeleton stands in for it
+const SKELETON_AFTER: Duration = Duration::from_millis(150);
+
+// How long the pointer rests before the controls hide
+const IDLE_AFTER: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub struct SlidesProps {
+    /// Index of the deck in the manifest
+    pub deck: usize,
     pub id: SlideId,
+    pub manifest: Rc<Manifest>,
+    /// When browsing by tag only the slides carrying it are stepped through
+    #[prop_or_default]
+    pub tag: Option<String>,
+    /// Step through hidden slides and drafts too
+    #[prop_or_default]
+    pub preview: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,6 +73,16 @@ pub enum SlideId {
 pub enum Direction {
     Back,
     Forward,
+    /// Past any fragments left on the slide
+    NextSlide,
+    PreviousSlide,
+    /// Between the sub-slides stacked beneath a slide
+    Down,
+    Up,
+    NextSection,
+    PreviousSection,
+    First,
+    Last,
 }
 
 pub enum Move {
@@ -35,10 +92,46 @@ pub enum Move {
 
 pub enum Msg {
     FetchMarkdown(Move),
-    ReceiveResponse(Result<String, anyhow::Error>),
+    /// The slide at the position and URL it was requested for
+    ReceiveResponse(Position, String, Result<SlideContent, FetchError>),
+    ShowSkeleton,
+    Retry,
+    RetryAfterBackoff,
+    ReceivePrefetch(String, Result<SlideContent, FetchError>),
+    ReceiveGlossary(Result<Glossary, anyhow::Error>),
+    ReceiveBibliography(Result<Bibliography, anyhow::Error>),
+    /// A slide the overview shows, by URL
+    ReceiveThumbnail(String, Result<SlideContent, FetchError>),
+    ReceiveKeymap(Keymap),
+    Key(Action),
+    Digit(u32),
+    CloseDialog,
+    CloseOverview,
+    PointerDown(PointerEvent),
+    PointerUp(PointerEvent),
+    PointerCancel,
+    Activity,
+    Idle,
     NoOp,
 }
 
+/// How a change of slide is recorded in the browser history
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum History {
+    /// A move through the deck, which Back undoes
+    Push,
+    /// The address of the slide already shown, such as a number swapped for the slug
+    Replace,
+}
+
+/// The dialog shown over the slide, if any
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialog {
+    None,
+    GoTo,
+    Help,
+}
+
 /// Have a look at:
 /// https://github.com/yewstack/yew/blob/66d506e13329a06f7dce0b55a9427972b8aad3ff/docs/concepts/services/fetch.md
 #[derive(Debug)]
@@ -46,11 +139,48 @@ pub struct SlidesModel {
     _keyboard_handle: Option<KeyListenerHandle>,
     props: SlidesProps,
     route_dispatcher: RouteAgentDispatcher,
+    store: Dispatcher<DeckStore>,
     link: ComponentLink<Self>,
     fetch_task: Option<FetchTask>,
-    fetch_result: Option<Html>,
-    slide: &'static Slide<'static>,
-    number: usize,
+    // The last slide rendered, kept on screen while the next one loads
+    shown: Option<(Position, Rc<Html>)>,
+    // Whether the next slide has taken long enough to show a skeleton for it
+    skeleton: bool,
+    _skeleton_task: Option<TimeoutTask>,
+    content: Option<SlideContent>,
+    // Why the current slide couldn't be fetched, and how often it was retried
+    error: Option<FetchError>,
+    retries: u32,
+    _retry_task: Option<TimeoutTask>,
+    slide_cache: SlideCache,
+    // Neighbours of the current slide being fetched ahead, by URL
+    prefetch_tasks: HashMap<String, FetchTask>,
+    render_cache: RenderCache,
+    // The overview's slides, kept apart so they don't push the presented ones out
+    thumbnail_cache: RenderCache,
+    context: RenderContext,
+    // Glossary and bibliography
+    _context_tasks: Vec<FetchTask>,
+    // Slides the overview shows that weren't in the slide cache, by URL. They land in
+    // the cache, so they aren't fetched ahead again
+    thumbnail_tasks: HashMap<String, FetchTask>,
+    // Where we are in the deck, every way of moving through it goes through the cursor
+    cursor: DeckCursor,
+    // Shared with the keyboard listener, which decides which keys the page takes over
+    keymap: Rc<RefCell<Keymap>>,
+    _keymap_task: FetchTask,
+    // Slide number typed so far, jumped to on Jump
+    typed_number: String,
+    dialog: Dialog,
+    // Touch that may turn into a swipe or a tap
+    touch: Option<gesture::Start>,
+    // Every slide stepped through, when the overview is shown in place of the slide
+    overview: Option<Vec<Thumbnail>>,
+    // The controls and the progress bar hide when the pointer rests
+    idle: bool,
+    _idle_task: Option<TimeoutTask>,
+    // Heading to scroll to once its slide is on screen, as linked to by a search result
+    anchor: Option<(Position, String)>,
 }
 
 impl Component for SlidesModel {
@@ -59,168 +189,1065 @@ impl Component for SlidesModel {
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         ConsoleService::log("New");
-        let callback = link.callback(|event: KeyboardEvent| match event.key().as_str() {
-            "ArrowLeft" => Msg::FetchMarkdown(Move::ByDirection(Direction::Back)),
-            "ArrowRight" => Msg::FetchMarkdown(Move::ByDirection(Direction::Forward)),
-            _ => Msg::NoOp,
+        let keymap = Rc::new(RefCell::new(Keymap::default()));
+        let bindings = keymap.clone();
+        let callback = link.callback(move |event: KeyboardEvent| {
+            let action = bindings.borrow().action(&event);
+            match action {
+                // The overview takes its rows itself, on a slide the keys scroll it
+                Some(Action::RowUp) | Some(Action::RowDown) => Msg::NoOp,
+                Some(action) => {
+                    // Keep Space and the page keys from scrolling too
+                    event.prevent_default();
+                    Msg::Key(action)
+                }
+                None => match keymap::digit(&event) {
+                    Some(digit) => Msg::Digit(digit),
+                    None => Msg::NoOp,
+                },
+            }
         });
 
         let keyboard_handle = KeyboardService::register_key_down(&yew::utils::document(), callback);
 
-        let (slide, number) = Self::get_slide(&props.id);
+        // App::slides only hands us slides that exist
+        let number = props
+            .manifest
+            .decks
+            .get(props.deck)
+            .and_then(|deck| Self::get_slide(deck, &props.id))
+            .unwrap_or(0);
+        let context_tasks = vec![
+            glossary::fetch(link.callback(Msg::ReceiveGlossary)),
+            bibliography::fetch(link.callback(Msg::ReceiveBibliography)),
+        ];
         let mut res = SlidesModel {
             fetch_task: None,
-            fetch_result: None,
+            shown: None,
+            skeleton: false,
+            _skeleton_task: None,
+            content: None,
+            error: None,
+            retries: 0,
+            _retry_task: None,
+            slide_cache: SlideCache::default(),
+            prefetch_tasks: HashMap::new(),
+            render_cache: RenderCache::default(),
+            thumbnail_cache: RenderCache::default(),
+            context: RenderContext::default(),
+            _context_tasks: context_tasks,
+            thumbnail_tasks: HashMap::new(),
             props,
-            number,
-            slide,
+            cursor: DeckCursor::new(vec![], Bounds::Clamp),
             route_dispatcher: RouteAgentDispatcher::new(),
+            store: DeckStore::dispatcher(),
+            keymap,
+            _keymap_task: keymap::fetch(link.callback(Msg::ReceiveKeymap)),
+            typed_number: String::new(),
+            dialog: Dialog::None,
+            touch: None,
+            overview: None,
+            idle: false,
+            _idle_task: None,
+            anchor: None,
             link,
             _keyboard_handle: Some(keyboard_handle),
         };
+        res.cursor = DeckCursor::new(res.layout(), Self::bounds(res.deck()));
+        res.cursor.goto(number);
+        res.anchor = location_anchor().map(|anchor| (res.position(), anchor));
+        res.update(Msg::Activity);
+        res.load_deck();
         res.fetch();
-        res.update_route();
+        res.update_route(History::Replace);
         res
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        ConsoleService::log("Update");
         use Msg::*;
         match msg {
             FetchMarkdown(mv) => {
-                let (slide, num) = match mv {
-                    Move::ByDirection(dir) => self.get_next(dir),
-                    Move::ById(id) => Self::get_slide(&id),
+                let from = self.cursor.position();
+                let moved = match mv {
+                    Move::ByDirection(Direction::Back) => self.cursor.prev(),
+                    Move::ByDirection(Direction::Forward) => self.cursor.next(),
+                    Move::ByDirection(Direction::NextSlide) => self.cursor.next_slide(),
+                    Move::ByDirection(Direction::PreviousSlide) => self.cursor.prev_slide(),
+                    Move::ByDirection(Direction::Down) => self.cursor.down(),
+                    Move::ByDirection(Direction::Up) => self.cursor.up(),
+                    Move::ByDirection(Direction::NextSection) => self.cursor.next_section(),
+                    Move::ByDirection(Direction::PreviousSection) => self.cursor.prev_section(),
+                    Move::ByDirection(Direction::First) => self.cursor.first(),
+                    Move::ByDirection(Direction::Last) => self.cursor.last(),
+                    Move::ById(id) => match Self::get_slide(self.deck(), &id) {
+                        Some(number) => self.cursor.goto(number),
+                        None => None,
+                    },
                 };
-                self.number = num;
-                self.slide = slide;
-
+                // Nothing moves at the boundaries of the deck, while revealing a fragment
+                // or moving between sub-slides only redraws the slide already shown
+                match moved {
+                    None => return false,
+                    Some(position) if position.slide == from.slide => return self.render(),
+                    Some(_) => {}
+                }
                 self.fetch();
+                // Every move gets its own entry, so Back returns to the previous slide
+                self.update_route(History::Push);
                 // we want to redraw so that the page displays a 'fetching...' message to the user
                 // so return 'true'
                 true
             }
-            ReceiveResponse(response) => {
+            ReceiveResponse(position, url, response) => {
+                // Keep what arrived for a slide we've already moved on from, but don't show it
+                let current = position == self.position() && url == self.slide().url;
                 match response {
-                    Ok(md) => {
-                        self.fetch_result = Some(markdown::generate_html(&md));
+                    Ok(content) => {
+                        self.slide_cache.insert(&url, content.clone());
+                        if !current {
+                            return false;
+                        }
+                        self.content = Some(content);
+                        self.render();
+                    }
+                    Err(_) if !current => return false,
+                    Err(error) => {
+                        ConsoleService::log(&error.to_string());
+                        // Transient failures are retried a few times, waiting longer each time
+                        if error.is_transient() && self.retries < MAX_RETRIES {
+                            let delay = RETRY_BACKOFF * 2u32.pow(self.retries);
+                            self.retries += 1;
+                            self._retry_task = Some(TimeoutService::spawn(
+                                delay,
+                                self.link.callback(|_| Msg::RetryAfterBackoff),
+                            ));
+                        }
+                        self.error = Some(error);
                     }
-                    Err(error) => ConsoleService::log(&error.to_string()),
                 }
                 self.fetch_task = None;
+                self._skeleton_task = None;
+                self.publish();
+                self.prefetch();
+                true
+            }
+            ShowSkeleton => {
+                self._skeleton_task = None;
+                self.skeleton = true;
+                true
+            }
+            Retry => {
+                self.retries = 0;
+                self._retry_task = None;
+                self.error = None;
+                self.request();
+                true
+            }
+            RetryAfterBackoff => {
+                self._retry_task = None;
+                self.error = None;
+                self.request();
+                true
+            }
+            ReceivePrefetch(url, response) => {
+                self.prefetch_tasks.remove(&url);
+                match response {
+                    Ok(content) => self.slide_cache.insert(&url, content),
+                    Err(error) => ConsoleService::log(&error.to_string()),
+                }
+                false
+            }
+            // The slide might have arrived before the deck-level data, so render it again
+            ReceiveGlossary(response) => match response {
+                Ok(glossary) => {
+                    self.context.glossary = glossary;
+                    self.clear_rendered();
+                    self.render()
+                }
+                Err(error) => {
+                    ConsoleService::log(&error.to_string());
+                    false
+                }
+            },
+            ReceiveBibliography(response) => match response {
+                Ok(bibliography) => {
+                    self.context.bibliography = bibliography;
+                    self.clear_rendered();
+                    self.render()
+                }
+                Err(error) => {
+                    ConsoleService::log(&error.to_string());
+                    false
+                }
+            },
+            ReceiveThumbnail(url, response) => {
+                self.thumbnail_tasks.remove(&url);
+                match response {
+                    Ok(content) => {
+                        self.slide_cache.insert(&url, content);
+                        self.render_thumbnails()
+                    }
+                    Err(error) => {
+                        ConsoleService::log(&error.to_string());
+                        false
+                    }
+                }
+            }
+            ReceiveKeymap(keymap) => {
+                *self.keymap.borrow_mut() = keymap;
+                self.dialog == Dialog::Help
+            }
+            Key(action) => self.key(action),
+            Digit(digit) => {
+                // The overview has no use for slide numbers
+                if self.overview.is_some() {
+                    return false;
+                }
+                self.typed_number.push_str(&digit.to_string());
                 true
             }
-            NoOp => false,
+            CloseDialog => {
+                self.dialog = Dialog::None;
+                true
+            }
+            CloseOverview => {
+                self.overview = None;
+                self.thumbnail_tasks.clear();
+                true
+            }
+            PointerDown(event) => {
+                self.update(Activity);
+                // Dialogs sit over the slide and take their own touches
+                if self.dialog == Dialog::None {
+                    self.touch = gesture::Start::new(&event);
+                }
+                false
+            }
+            PointerUp(event) => match self.touch.take().and_then(|start| start.end(&event)) {
+                Some(Gesture::Back) => {
+                    self.update(FetchMarkdown(Move::ByDirection(Direction::Back)))
+                }
+                Some(Gesture::Forward) => {
+                    self.update(FetchMarkdown(Move::ByDirection(Direction::Forward)))
+                }
+                None => false,
+            },
+            PointerCancel => {
+                self.touch = None;
+                false
+            }
+            Activity => {
+                self._idle_task = Some(TimeoutService::spawn(
+                    IDLE_AFTER,
+                    self.link.callback(|_| Msg::Idle),
+                ));
+                std::mem::replace(&mut self.idle, false)
+            }
+            Idle => {
+                self.idle = true;
+                true
+            }
+            NoOp => {
+                // Any other key abandons the number being typed
+                if self.typed_number.is_empty() {
+                    false
+                } else {
+                    self.typed_number.clear();
+                    true
+                }
+            }
         }
     }
 
     fn view(&self) -> Html {
-        html! {
+        let idle = if self.idle { "is-idle" } else { "" };
+        if let Some(thumbnails) = &self.overview {
+            let on_select = self.link.batch_callback(|index| {
+                vec![
+                    Msg::CloseOverview,
+                    Msg::FetchMarkdown(Move::ById(SlideId::Num(index))),
+                ]
+            });
+            return html! {
+                <div class=("slides", idle) onpointermove=self.link.callback(|_| Msg::Activity)>
+                    <ProgressBar />
+                    <div class="hero-body">
+                        <Overview
+                            thumbnails=thumbnails.clone()
+                            current=self.number()
+                            on_select=on_select
+                            keymap=self.keymap.borrow().clone()
+                        />
+                        { self.view_dialog() }
+                    </div>
+                </div>
+            };
+        }
 
-            <div class={"hero-body"}>
-                <p class={"title"}>{ self.slide.title }</p>
-                <div class={"container"}>
-                    { self.view_result() }
+        // The slide we're leaving stays on screen until the next one has rendered,
+        // or until loading it takes long enough to show a skeleton instead
+        let (slide, body) = match &self.shown {
+            Some((position, html)) if *position == self.position() => {
+                (self.slide(), html! { <CachedHtml html=html.clone() /> })
+            }
+            Some((position, html)) if self.fetch_task.is_some() && !self.skeleton => {
+                match self.slide_at(*position) {
+                    Some(slide) => (slide, html! { <CachedHtml html=html.clone() /> }),
+                    None => (self.slide(), html! {}),
+                }
+            }
+            _ => match &self.error {
+                Some(error) => (self.slide(), self.view_error(error)),
+                None if self.skeleton => (self.slide(), view_skeleton()),
+                None => (self.slide(), html! {}),
+            },
+        };
+        let transition = match slide.transition {
+            Transition::None => "",
+            Transition::Fade => "slide--fade",
+            Transition::Slide => "slide--slide",
+            Transition::Zoom => "slide--zoom",
+        };
+        let class = slide.class.clone().unwrap_or_default();
+        let style = slide.background_style().unwrap_or_default();
+        html! {
+            <div class=("slides", idle) onpointermove=self.link.callback(|_| Msg::Activity)>
+                <ProgressBar />
+                // Keyed by the slide so the transition plays for every slide
+                <div
+                    class=("hero-body", "slide", transition, class)
+                    style=style
+                    key=slide.slug.clone()
+                    onpointerdown=self.link.callback(Msg::PointerDown)
+                    onpointerup=self.link.callback(Msg::PointerUp)
+                    onpointercancel=self.link.callback(|_| Msg::PointerCancel)
+                >
+                    { self.view_tag() }
+                    { view_meta(slide) }
+                    <p class={"title"}>{ &slide.title }</p>
+                    <div class={"container"}>
+                        { body }
+                    </div>
+                    { self.view_typed_number() }
+                    { self.view_dialog() }
                 </div>
+                <SlideControls
+                    on_back=self.link.callback(|_| Msg::FetchMarkdown(Move::ByDirection(Direction::Back)))
+                    on_forward=self.link.callback(|_| Msg::FetchMarkdown(Move::ByDirection(Direction::Forward)))
+                />
             </div>
+        }
+    }
 
-
-            // <div class="slides--wrapper">
-            //     { self.view_data() }
-            //     <div class="slides-navigation">
-            //         <button disabled={ self.props.number == 0 } class="left" onclick=self.link.callback(|_| Msg::Left) style={if self.props.number == 0 {"visibility:hidden;"} else {"visibility:visible;"}}>
-            //             <i  class="arrow arrow-left"></i>
-            //         </button>
-            //         <button disabled={ self.props.number == MAX_COUNT } class="right" onclick=self.link.callback(|_| Msg::Right) style={if self.props.number == MAX_COUNT {"visibility:hidden;"} else {"visibility:visible;"}}>
-            //             <i class="arrow arrow-right"></i>
-            //         </button>
-            //     </div>
-            // </div>
+    fn rendered(&mut self, _first_render: bool) {
+        // The heading can only be found once the slide it's on is mounted
+        let anchor = match (&self.anchor, &self.shown) {
+            (Some((target, anchor)), Some((shown, _))) if target == shown => anchor.clone(),
+            _ => return,
+        };
+        self.anchor = None;
+        if let Some(element) = yew::utils::document().get_element_by_id(&anchor) {
+            element.scroll_into_view();
         }
     }
 
+    fn destroy(&mut self) {
+        self.store.send(Request::ClearPosition);
+    }
+
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
-        if self.props != props && self.fetch_task.is_none() {
-            let (slide, number) = Self::get_slide(&props.id);
-            self.props = props;
-            self.slide = slide;
-            self.number = number;
-            self.fetch();
-            self.update_route();
-            true
+        let anchor = location_anchor();
+        if self.props == props {
+            // A search result for a heading of the slide we already show
+            return match anchor {
+                Some(anchor) => {
+                    self.anchor = Some((self.position(), anchor));
+                    self.update_route(History::Replace);
+                    true
+                }
+                None => false,
+            };
+        }
+        let number = props
+            .manifest
+            .decks
+            .get(props.deck)
+            .and_then(|deck| Self::get_slide(deck, &props.id));
+        let deck_changed = self.props.deck != props.deck || self.props.manifest != props.manifest;
+        self.props = props;
+        if deck_changed {
+            self.cursor = DeckCursor::new(self.layout(), Self::bounds(self.deck()));
+            self.load_deck();
+            self.fetch_thumbnails();
+            self.render_thumbnails();
         } else {
-            false
+            // The tag or preview mode may have changed which slides are stepped onto
+            self.cursor.relayout(self.layout());
+        }
+        // Back and Forward move the deck from here, while following an alias or our own
+        // route change leads to the slide we already show
+        match number {
+            Some(number) if deck_changed || number != self.number() => {
+                self.cursor.goto(number);
+                self.fetch();
+            }
+            _ => {}
+        }
+        if let Some(anchor) = anchor {
+            self.anchor = Some((self.position(), anchor));
         }
+        // The tag may have changed how many slides there are to step through
+        self.publish();
+        self.update_route(History::Replace);
+        true
     }
 }
 
 impl SlidesModel {
-    fn update_route(&mut self) {
-        let route = AppRoute::SlidesName(self.slide.slug.to_string());
-        self.route_dispatcher
-            .send(RouteRequest::ChangeRoute(route.into_route()));
-    }
-
-    fn get_next(&self, dir: Direction) -> (&'static Slide<'static>, usize) {
-        use Direction::*;
-        match dir {
-            Back => {
-                if self.number > 0 {
-                    let num = self.number - 1;
-                    (SLIDES.get(num).unwrap(), num)
-                } else {
-                    (self.slide, self.number)
+    /// Render the slides of the current deck with its citations, the manifest lists
+    /// them so no slide has to be fetched to number them
+    fn load_deck(&mut self) {
+        let deck = &self.props.manifest.decks[self.props.deck];
+        self.context.deck = deck.id.clone();
+        self.context.citations = Citations::new(deck.citations.clone());
+        self.thumbnail_cache = RenderCache::new(deck.slides.len());
+        self.thumbnail_tasks.clear();
+        self.clear_rendered();
+    }
+
+    // The deck-level data the slides are rendered with has changed
+    fn clear_rendered(&mut self) {
+        self.render_cache.clear();
+        self.thumbnail_cache.clear();
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            deck: self.props.deck,
+            slide: self.number(),
+        }
+    }
+
+    fn deck(&self) -> &Deck {
+        &self.props.manifest.decks[self.props.deck]
+    }
+
+    fn slide(&self) -> &Slide {
+        &self.deck().slides[self.number()]
+    }
+
+    /// Index in the deck of the slide on the cursor
+    fn number(&self) -> usize {
+        self.cursor.slide()
+    }
+
+    /// The slide a deck opens on, the first one its cursor steps onto
+    pub fn first_slide(deck: &Deck, preview: bool) -> usize {
+        DeckCursor::new(Self::deck_layout(deck, None, preview), Self::bounds(deck)).slide()
+    }
+
+    fn layout(&self) -> Vec<SlideLayout> {
+        Self::deck_layout(self.deck(), self.props.tag.as_deref(), self.props.preview)
+    }
+
+    // The deck as the cursor moves through it
+    fn deck_layout(deck: &Deck, tag: Option<&str>, preview: bool) -> Vec<SlideLayout> {
+        // A slide naming a section starts it, the slides after it belong to it
+        let mut section = 0;
+        deck.slides
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| {
+                if index > 0 && slide.section.is_some() {
+                    section += 1;
                 }
-            }
-            Forward => {
-                if self.number < SLIDES.len() - 1 {
-                    let num = self.number + 1;
-                    (SLIDES.get(num).unwrap(), num)
-                } else {
-                    (self.slide, self.number)
+                SlideLayout {
+                    fragments: slide.fragments.clone(),
+                    section,
+                    skipped: !Self::is_stepped(slide, tag, preview),
                 }
-            }
+            })
+            .collect()
+    }
+
+    fn bounds(deck: &Deck) -> Bounds {
+        if deck.loops {
+            Bounds::Loop
+        } else {
+            Bounds::Clamp
         }
     }
 
-    fn get_slide(id: &SlideId) -> (&'static Slide<'static>, usize) {
-        let number = match id {
-            SlideId::Str(s) => SLIDES.iter().position(|x| x.slug == s).unwrap_or(0),
-            SlideId::Num(n) => {
-                if n >= &SLIDES.len() {
-                    0
-                } else {
-                    n.clone()
+    // The slide at a position, which may be in another deck than the current one
+    fn slide_at(&self, position: Position) -> Option<&Slide> {
+        self.props
+            .manifest
+            .decks
+            .get(position.deck)?
+            .slides
+            .get(position.slide)
+    }
+
+    /// Make the address bar show the current slide
+    fn update_route(&mut self, history: History) {
+        let deck = self.deck().id.clone();
+        let slug = self.slide().slug.clone();
+        let route = match &self.props.tag {
+            Some(tag) => AppRoute::tag_slides(tag, deck, slug),
+            None => AppRoute::DeckSlidesName(deck, slug),
+        };
+        let mut route = route.into_route();
+        if self.props.preview {
+            route.route.push_str("?preview");
+        }
+        // Numbers, aliases and the old routes are swapped for the slug in the history,
+        // and the heading linked to is dropped once we've taken it
+        let location = yew::utils::window().location();
+        let current = format!(
+            "{}{}{}",
+            location.pathname().unwrap_or_default(),
+            location.search().unwrap_or_default(),
+            location.hash().unwrap_or_default()
+        );
+        if route.route != current {
+            let request = match history {
+                History::Push => RouteRequest::ChangeRoute(route),
+                History::Replace => RouteRequest::ReplaceRoute(route),
+            };
+            self.route_dispatcher.send(request);
+        }
+    }
+
+    // Act on a bound key, any key but Jump abandons the number being typed
+    fn key(&mut self, action: Action) -> ShouldRender {
+        let typed_number = std::mem::take(&mut self.typed_number);
+        let mv = |dir| Msg::FetchMarkdown(Move::ByDirection(dir));
+        // The overview takes the keys for moving around its grid itself
+        let moves = matches!(
+            action,
+            Action::Next
+                | Action::Previous
+                | Action::NextSlide
+                | Action::PreviousSlide
+                | Action::Down
+                | Action::Up
+                | Action::NextSection
+                | Action::PreviousSection
+                | Action::First
+                | Action::Last
+                | Action::Jump
+                | Action::RowUp
+                | Action::RowDown
+        );
+        if moves && self.overview.is_some() {
+            return false;
+        }
+        match action {
+            Action::Next => self.update(mv(Direction::Forward)),
+            Action::Previous => self.update(mv(Direction::Back)),
+            Action::NextSlide => self.update(mv(Direction::NextSlide)),
+            Action::PreviousSlide => self.update(mv(Direction::PreviousSlide)),
+            Action::Down => self.update(mv(Direction::Down)),
+            Action::Up => self.update(mv(Direction::Up)),
+            Action::NextSection => self.update(mv(Direction::NextSection)),
+            Action::PreviousSection => self.update(mv(Direction::PreviousSection)),
+            Action::First => self.update(mv(Direction::First)),
+            Action::Last => self.update(mv(Direction::Last)),
+            Action::Jump => match typed_number.parse::<usize>() {
+                // Slides are numbered from 1 on screen
+                Ok(number) if number > 0 => {
+                    let id = SlideId::Num(number - 1);
+                    self.update(Msg::FetchMarkdown(Move::ById(id)))
                 }
+                _ => !typed_number.is_empty(),
+            },
+            Action::GoTo => {
+                self.dialog = Dialog::GoTo;
+                true
             }
-        };
-        (SLIDES.get(number).unwrap(), number)
+            Action::Help => {
+                self.dialog = match self.dialog {
+                    Dialog::Help => Dialog::None,
+                    _ => Dialog::Help,
+                };
+                true
+            }
+            Action::Close if self.dialog != Dialog::None => {
+                self.dialog = Dialog::None;
+                true
+            }
+            Action::Close | Action::Overview => {
+                self.overview = match self.overview {
+                    Some(_) => None,
+                    None => Some(vec![]),
+                };
+                self.fetch_thumbnails();
+                self.render_thumbnails();
+                true
+            }
+            Action::RowUp | Action::RowDown | Action::None => !typed_number.is_empty(),
+        }
+    }
+
+    /// Whether stepping through the deck stops at the slide
+    fn is_stepped(slide: &Slide, tag: Option<&str>, preview: bool) -> bool {
+        slide.is_listed(preview) && tag.is_none_or(|tag| slide.has_tag(tag))
+    }
+
+    fn stepped(&self, slide: &Slide) -> bool {
+        Self::is_stepped(slide, self.props.tag.as_deref(), self.props.preview)
+    }
+
+    /// Index of the slide in the deck, slugs are looked up in the aliases too
+    pub fn get_slide(deck: &Deck, id: &SlideId) -> Option<usize> {
+        match id {
+            SlideId::Str(s) => deck.slide_index(s),
+            SlideId::Num(n) if *n < deck.slides.len() => Some(*n),
+            SlideId::Num(_) => None,
+        }
     }
 
     fn fetch(&mut self) {
-        // 1. build the request
-        let request = Request::get(self.slide.url)
-            .body(Nothing)
-            .expect("Could not build request.");
-        // 2. construct a callback
+        // Dropping the tasks of the slide we're leaving cancels its request and retry
+        self.fetch_task = None;
+        self._retry_task = None;
+        self._skeleton_task = None;
+        self.skeleton = false;
+        self.content = None;
+        self.error = None;
+        self.retries = 0;
+        // The References slide is generated from the bibliography we already hold
+        if self.slide().is_references() {
+            self.render();
+            self.publish();
+            self.prefetch();
+            return;
+        }
+        // A slide we've seen or fetched ahead shows straight away
+        let url = self.slide().url.clone();
+        if let Some(content) = self.slide_cache.get(&url) {
+            self.content = Some(content);
+            self.render();
+            self.publish();
+            self.prefetch();
+            return;
+        }
+        self.request();
+    }
+
+    /// Ask the server for the current slide
+    fn request(&mut self) {
+        self._skeleton_task = Some(TimeoutService::spawn(
+            SKELETON_AFTER,
+            self.link.callback(|_| Msg::ShowSkeleton),
+        ));
+        let url = self.slide().url.clone();
+        let position = self.position();
+        let key = url.clone();
         let callback = self
             .link
-            .callback(|response: Response<Result<String, anyhow::Error>>| {
-                Msg::ReceiveResponse(response.into_body())
+            .callback(move |content| Msg::ReceiveResponse(position, key.clone(), content));
+        // store the task so it isn't canceled immediately
+        self.fetch_task = Some(slides_data::fetch_text(&url, callback));
+        self.publish();
+    }
+
+    /// Fetch the slides either side of the current one into the cache, the ones
+    /// no longer next to it are dropped
+    fn prefetch(&mut self) {
+        if slide_cache::saves_data() {
+            self.prefetch_tasks.clear();
+            return;
+        }
+        let deck = &self.props.manifest.decks[self.props.deck];
+        let urls: Vec<String> = self
+            .cursor
+            .around(deck.prefetch())
+            .into_iter()
+            .map(|i| &deck.slides[i])
+            .filter(|slide| !slide.is_references())
+            .map(|slide| slide.url.clone())
+            // Slides the overview asked for are on their way already
+            .filter(|url| {
+                !self.slide_cache.contains(url) && !self.thumbnail_tasks.contains_key(url)
+            })
+            .collect();
+
+        self.prefetch_tasks.retain(|url, _| urls.contains(url));
+        for url in urls {
+            if !self.prefetch_tasks.contains_key(&url) {
+                let key = url.clone();
+                let callback = self
+                    .link
+                    .callback(move |content| Msg::ReceivePrefetch(key.clone(), content));
+                let task = slides_data::fetch_text(&url, callback);
+                self.prefetch_tasks.insert(url, task);
+            }
+        }
+    }
+
+    // Tell the store where we are
+    fn publish(&mut self) {
+        let position = self.position();
+        let fetch = match &self.error {
+            _ if self.fetch_task.is_some() => FetchState::Fetching,
+            Some(error) => FetchState::Failed(error.clone()),
+            None => FetchState::Done,
+        };
+        self.store
+            .send(Request::SetPosition(position, self.cursor.progress()));
+        self.store.send(Request::SetFetch(fetch));
+    }
+
+    /// Render the current slide with the deck-level data we have so far
+    fn render(&mut self) -> ShouldRender {
+        self.render_thumbnails();
+        let slide = &self.props.manifest.decks[self.props.deck].slides[self.cursor.slide()];
+        if slide.is_references() {
+            let html = Rc::new(bibliography::view_references(
+                &self.context.bibliography,
+                &self.context.citations,
+            ));
+            self.shown = Some((self.position(), html));
+            true
+        } else if let Some(content) = &self.content {
+            let text = on_screen(&content.text, slide, self.cursor.position());
+            let html = render_slide(
+                &mut self.render_cache,
+                &self.context,
+                slide,
+                content.content_type.as_deref(),
+                &text,
+            );
+            self.shown = Some((self.position(), html));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Fetch the slides the overview shows that aren't cached, the ones still on their
+    /// way are dropped with the overview
+    fn fetch_thumbnails(&mut self) {
+        if self.overview.is_none() {
+            self.thumbnail_tasks.clear();
+            return;
+        }
+        let deck = &self.props.manifest.decks[self.props.deck];
+        let urls: Vec<String> = deck
+            .slides
+            .iter()
+            .filter(|slide| self.stepped(slide) && !slide.is_references())
+            .map(|slide| slide.url.clone())
+            .filter(|url| !self.slide_cache.contains(url))
+            .collect();
+        for url in urls {
+            if !self.thumbnail_tasks.contains_key(&url) {
+                let key = url.clone();
+                let callback = self
+                    .link
+                    .callback(move |content| Msg::ReceiveThumbnail(key.clone(), content));
+                let task = slides_data::fetch_text(&url, callback);
+                self.thumbnail_tasks.insert(url, task);
+            }
+        }
+    }
+
+    /// Render every slide the overview shows, as far as their text has arrived
+    fn render_thumbnails(&mut self) -> ShouldRender {
+        if self.overview.is_none() {
+            return false;
+        }
+        let deck = &self.props.manifest.decks[self.props.deck];
+        let stepped: Vec<usize> = (0..deck.slides.len())
+            .filter(|&index| self.stepped(&deck.slides[index]))
+            .collect();
+        let mut thumbnails = vec![];
+        for index in stepped {
+            let slide = &deck.slides[index];
+            let html = if slide.is_references() {
+                Some(Rc::new(bibliography::view_references(
+                    &self.context.bibliography,
+                    &self.context.citations,
+                )))
+            } else {
+                match self.slide_cache.get(&slide.url) {
+                    Some(content) => Some(render_thumbnail(
+                        &mut self.thumbnail_cache,
+                        &self.context,
+                        slide,
+                        content.content_type.as_deref(),
+                        &content.text,
+                    )),
+                    None => None,
+                }
+            };
+            thumbnails.push(Thumbnail {
+                index,
+                title: slide.title.clone(),
+                html,
             });
-        // 3. pass the request and callback to the fetch service
-        let task = FetchService::fetch(request, callback).expect("failed to start request");
-        // 4. store the task so it isn't canceled immediately
-        self.fetch_task = Some(task);
+        }
+        self.overview = Some(thumbnails);
+        true
+    }
+
+    // Shows the tag being browsed by, with a way back to the whole deck
+    fn view_tag(&self) -> Html {
+        match &self.props.tag {
+            Some(tag) => {
+                let deck =
+                    AppRoute::DeckSlidesName(self.deck().id.clone(), self.slide().slug.clone());
+                html! {
+                    <div class="tags has-addons">
+                        <AppAnchor classes="tag is-primary" route=AppRoute::tag(tag)>
+                            { tag }
+                        </AppAnchor>
+                        <AppAnchor classes="tag is-delete" route=deck />
+                    </div>
+                }
+            }
+            None => html! {},
+        }
     }
 
-    fn view_result(&self) -> Html {
-        if self.fetch_task.is_some() {
-            html! { <p>{ "Fetching data..." }</p> }
+    // Shows the slide number being typed until it's jumped to
+    fn view_typed_number(&self) -> Html {
+        if self.typed_number.is_empty() {
+            html! {}
         } else {
-            self.fetch_result
-                .clone()
-                .unwrap_or(html! {<p> {"<p>Data not loaded...</p>"} </p>})
+            html! {
+                <div class="notification is-info slide__typed-number">
+                    { format!("Go to {}…", self.typed_number) }
+                </div>
+            }
+        }
+    }
+
+    fn view_dialog(&self) -> Html {
+        let on_close = self.link.callback(|_| Msg::CloseDialog);
+        match self.dialog {
+            Dialog::None => html! {},
+            Dialog::GoTo => {
+                let titles = self
+                    .deck()
+                    .slides
+                    .iter()
+                    .map(|slide| slide.title.clone())
+                    .collect::<Vec<_>>();
+                let on_select = self.link.batch_callback(|index| {
+                    vec![
+                        Msg::CloseDialog,
+                        Msg::FetchMarkdown(Move::ById(SlideId::Num(index))),
+                    ]
+                });
+                html! { <GoTo titles=titles on_select=on_select on_close=on_close /> }
+            }
+            Dialog::Help => html! {
+                <Shortcuts keymap=self.keymap.borrow().clone() on_close=on_close />
+            },
+        }
+    }
+
+    fn view_error(&self, error: &FetchError) -> Html {
+        let heading = match error {
+            FetchError::Network(_) => "Network error",
+            FetchError::NotFound => "Slide not found",
+            FetchError::Server(_) => "Server error",
+            FetchError::Parse(_) => "Unreadable slide",
+        };
+        let retrying = if self._retry_task.is_some() {
+            html! { <p class="is-size-7">{ "Trying again shortly…" }</p> }
+        } else {
+            html! {}
+        };
+        html! {
+            <article class="message is-danger slide__error">
+                <div class="message-header">
+                    <p>{ heading }</p>
+                </div>
+                <div class="message-body">
+                    <p>{ error.to_string() }</p>
+                    <p class="is-size-7">{ &self.slide().url }</p>
+                    { retrying }
+                    <button class="button is-danger is-outlined" onclick=self.link.callback(|_| Msg::Retry)>
+                        { "Retry" }
+                    </button>
+                </div>
+            </article>
+        }
+    }
+}
+
+// The heading the address links to, if any
+fn location_anchor() -> Option<String> {
+    let hash = yew::utils::window().location().hash().ok()?;
+    Some(hash.trim_start_matches('#').to_string()).filter(|anchor| !anchor.is_empty())
+}
+
+// The part of the slide the cursor is on, its sub-slide with the fragments revealed so
+// far. A slide without any counted steps is shown whole.
+fn on_screen(text: &str, slide: &Slide, position: CursorPosition) -> String {
+    if slide.fragments.is_empty() {
+        return text.to_string();
+    }
+    markup::steps(text)
+        .get(position.vertical)
+        .map(|fragments| {
+            fragments
+                .iter()
+                .take(position.fragment + 1)
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Render a slide through the cache, which hands back the same tree for the same text
+fn render_slide(
+    cache: &mut RenderCache,
+    context: &RenderContext,
+    slide: &Slide,
+    content_type: Option<&str>,
+    text: &str,
+) -> Rc<Html> {
+    let format = slide.format(content_type);
+    cache.get_or_render(&slide.url, text, |text| generate(format, text, context))
+}
+
+// Render a slide for the overview, where the ids of its headings and glossary terms
+// would repeat between the thumbnails
+fn render_thumbnail(
+    cache: &mut RenderCache,
+    context: &RenderContext,
+    slide: &Slide,
+    content_type: Option<&str>,
+    text: &str,
+) -> Rc<Html> {
+    let format = slide.format(content_type);
+    cache.get_or_render(&slide.url, text, |text| {
+        let mut html = generate(format, text, context);
+        remove_ids(&mut html);
+        html
+    })
+}
+
+fn generate(format: Format, text: &str, context: &RenderContext) -> Html {
+    match format {
+        Format::Html => html_slide::generate_html(text, context),
+        _ => markdown::generate_html(text, context),
+    }
+}
+
+// Drop the ids in the tree and the references to them
+fn remove_ids(node: &mut VNode) {
+    match node {
+        VNode::VTag(tag) => {
+            tag.attributes.remove("id");
+            tag.attributes.remove("aria-describedby");
+            tag.children.iter_mut().for_each(remove_ids);
         }
+        VNode::VList(list) => list.iter_mut().for_each(remove_ids),
+        _ => {}
+    }
+}
+
+// Shows how long the slide should take, and whether it's hidden or a draft
+fn view_meta(slide: &Slide) -> Html {
+    let duration = match slide.duration {
+        Some(seconds) => html! {
+            <span class="tag" title="Estimated speaking time">
+                { format!("{}:{:02}", seconds / 60, seconds % 60) }
+            </span>
+        },
+        None => html! {},
+    };
+    let draft = if slide.draft {
+        html! { <span class="tag is-warning">{ "Draft" }</span> }
+    } else {
+        html! {}
+    };
+    let hidden = if slide.hidden {
+        html! { <span class="tag is-light">{ "Hidden" }</span> }
+    } else {
+        html! {}
+    };
+    html! {
+        <div class="tags slide__meta">
+            { duration }
+            { draft }
+            { hidden }
+        </div>
+    }
+}
+
+// Placeholder for the body of a slide that takes a while to load
+fn view_skeleton() -> Html {
+    html! {
+        <div class="skeleton" aria-busy="true" aria-label="Loading the slide">
+            <div class="skeleton__line"></div>
+            <div class="skeleton__line"></div>
+            <div class="skeleton__line skeleton__line--short"></div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yew::virtual_dom::VTag;
+
+    #[test]
+    fn thumbnails_lose_their_ids() {
+        let mut heading = VTag::new("h2");
+        heading.add_attribute("id", &"ownership");
+        let mut term = VTag::new("span");
+        term.add_attribute("aria-describedby", &"glossary-box-t");
+        term.add_attribute("class", &"glossary-term");
+        let mut html =
+            html! { <div id="slide">{ VNode::from(heading) }<>{ VNode::from(term) }</></div> };
+
+        remove_ids(&mut html);
+        let div = match &html {
+            VNode::VTag(div) => div,
+            _ => panic!("expected the div"),
+        };
+        assert!(!div.attributes.contains_key("id"));
+        let heading = match &div.children[0] {
+            VNode::VTag(heading) => heading,
+            _ => panic!("expected the heading"),
+        };
+        assert!(heading.attributes.is_empty());
+        let term = match &div.children[1] {
+            VNode::VList(list) => match &list[0] {
+                VNode::VTag(term) => term,
+                _ => panic!("expected the term"),
+            },
+            _ => panic!("expected the fragment"),
+        };
+        assert_eq!(term.attributes.len(), 1);
+        assert_eq!(term.attributes["class"], "glossary-term");
+    }
+
+    #[test]
+    fn decks_open_on_their_first_listed_slide() {
+        let slide = |slug: &str, hidden: bool| Slide {
+            slug: slug.to_string(),
+            hidden,
+            ..Slide::default()
+        };
+        let deck = Deck {
+            id: "rust".to_string(),
+            title: "Rust".to_string(),
+            description: None,
+            date: None,
+            prefetch: None,
+            loops: false,
+            citations: vec![],
+            slides: vec![slide("notes", true), slide("intro", false)],
+        };
+
+        assert_eq!(SlidesModel::first_slide(&deck, false), 1);
+        assert_eq!(SlidesModel::first_slide(&deck, true), 0);
     }
 }
@@ -0,0 +1,116 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::{
+    slides_data::Manifest,
+    switch::{AppAnchor, AppRoute},
+};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    pub manifest: Rc<Manifest>,
+    /// Without a tag every tag is listed, with one the slides carrying it
+    #[prop_or_default]
+    pub tag: Option<String>,
+    /// Hidden slides and drafts are listed as well
+    #[prop_or_default]
+    pub preview: bool,
+}
+
+pub struct TagsModel {
+    props: Props,
+}
+
+impl Component for TagsModel {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        let content = match &self.props.tag {
+            Some(tag) => self.view_tag(tag),
+            None => self.view_tags(),
+        };
+        html! {
+            <div class="hero-body">
+                <div class="container content">
+                    { content }
+                </div>
+            </div>
+        }
+    }
+}
+
+impl TagsModel {
+    fn view_tags(&self) -> Html {
+        let tags = self.props.manifest.tags(self.props.preview);
+        let tags = tags.iter().map(|(tag, count)| {
+            html! {
+                <AppAnchor classes="tags has-addons" route=AppRoute::tag(tag)>
+                    <span class="tag is-primary">{ tag }</span>
+                    <span class="tag">{ count }</span>
+                </AppAnchor>
+            }
+        });
+        html! {
+            <>
+                <h1 class="title">{ "Tags" }</h1>
+                <div class="field is-grouped is-grouped-multiline">
+                    { for tags }
+                </div>
+            </>
+        }
+    }
+
+    fn view_tag(&self, tag: &str) -> Html {
+        let decks = self.props.manifest.decks.iter().filter_map(|deck| {
+            let slides = deck
+                .slides
+                .iter()
+                .filter(|slide| slide.is_listed(self.props.preview) && slide.has_tag(tag))
+                .map(|slide| {
+                    let route = AppRoute::tag_slides(tag, deck.id.clone(), slide.slug.clone());
+                    html! {
+                        <li><AppAnchor route=route>{ &slide.title }</AppAnchor></li>
+                    }
+                })
+                .collect::<Vec<Html>>();
+            if slides.is_empty() {
+                None
+            } else {
+                Some(html! {
+                    <>
+                        <h2 class="subtitle">{ &deck.title }</h2>
+                        <ol>{ for slides }</ol>
+                    </>
+                })
+            }
+        });
+        let decks = decks.collect::<Vec<Html>>();
+        let body = if decks.is_empty() {
+            html! { <p>{ "No slides carry this tag." }</p> }
+        } else {
+            html! { <>{ for decks }</> }
+        };
+        html! {
+            <>
+                <h1 class="title">{ format!("Tagged \"{}\"", tag) }</h1>
+                { body }
+                <AppAnchor route=AppRoute::Tags>{ "All tags" }</AppAnchor>
+            </>
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use yew::Html;
+
+use crate::lru::Lru;
+
+/// How many rendered slides we keep, enough for a whole talk
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Rendered slides keyed by their URL and a hash of the content they were rendered from.
+/// The least recently used entry is evicted once the cache is full.
+#[derive(Debug)]
+pub struct RenderCache {
+    // The hash of the content along with what it rendered to
+    entries: Lru<String, (u64, Rc<Html>)>,
+}
+
+impl RenderCache {
+    pub fn new(capacity: usize) -> Self {
+        RenderCache {
+            entries: Lru::new(capacity),
+        }
+    }
+
+    /// Get the rendered slide, or render it if the content isn't cached.
+    /// An entry for the same URL with different content is replaced.
+    pub fn get_or_render(
+        &mut self,
+        url: &str,
+        content: &str,
+        render: impl FnOnce(&str) -> Html,
+    ) -> Rc<Html> {
+        let hash = content_hash(content);
+        match self.entries.get(url) {
+            Some((cached, html)) if *cached == hash => return html.clone(),
+            _ => {}
+        }
+
+        let html = Rc::new(render(content));
+        self.entries.insert(url.to_string(), (hash, html.clone()));
+        html
+    }
+
+    /// Drop everything, e.g. when the deck-level data the slides are rendered with changes
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,279 @@
+/// Full-text search over the slides, using the index build.rs makes of them.
+///
+/// Every word of the query has to match the start of a word in the slide, slides are
+/// ranked by how much weight the index gives the matched words.
+/// The index is made from the slides in static/api/slide, a deployed deck.json that
+/// renames slides is not reflected in it.
+use std::collections::{BTreeMap, HashMap};
+
+use ankan_common::text;
+use serde::Deserialize;
+
+const GENERATED_INDEX: &str = include_str!(concat!(env!("OUT_DIR"), "/search.json"));
+
+// Characters of context shown on either side of the match
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Section {
+    pub heading: Option<String>,
+    /// Id of the heading on the rendered slide
+    pub anchor: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Document {
+    pub deck: String,
+    pub deck_title: String,
+    pub slug: String,
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    /// Word to the (document, section, weight) of every section using it
+    terms: BTreeMap<String, Vec<(usize, usize, u32)>>,
+}
+
+// Score of each document and the weight of each of its sections
+type Scores = HashMap<usize, (u32, HashMap<usize, u32>)>;
+
+/// A slide matching the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit<'a> {
+    pub document: &'a Document,
+    /// The section matching the query best
+    pub section: &'a Section,
+    pub snippet: Snippet,
+}
+
+/// Text around the first match in a section, `matched` is to be highlighted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snippet {
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+impl SearchIndex {
+    pub fn generated() -> Self {
+        serde_json::from_str(GENERATED_INDEX).expect("build.rs generates a valid search index")
+    }
+
+    /// The best `limit` slides for the query, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Hit<'_>> {
+        let words: Vec<String> = text::tokenize(query).collect();
+        if words.is_empty() {
+            return vec![];
+        }
+
+        let mut documents: Option<Scores> = None;
+        for word in &words {
+            let mut matches = Scores::new();
+            let prefixed = self
+                .terms
+                .range(word.clone()..)
+                .take_while(|(term, _)| term.starts_with(word.as_str()));
+            for (term, postings) in prefixed {
+                // A whole word counts for more than a prefix of a longer one
+                let boost = if term == word { 2 } else { 1 };
+                for &(doc, section, weight) in postings {
+                    let (score, sections) = matches.entry(doc).or_default();
+                    *score += weight * boost;
+                    *sections.entry(section).or_insert(0) += weight * boost;
+                }
+            }
+            // Only the documents matching every word are kept
+            documents = Some(match documents {
+                None => matches,
+                Some(mut documents) => {
+                    documents.retain(|doc, _| matches.contains_key(doc));
+                    for (doc, (score, sections)) in matches {
+                        if let Some(entry) = documents.get_mut(&doc) {
+                            entry.0 += score;
+                            for (section, weight) in sections {
+                                *entry.1.entry(section).or_insert(0) += weight;
+                            }
+                        }
+                    }
+                    documents
+                }
+            });
+        }
+
+        let mut ranked: Vec<(usize, u32, usize)> = documents
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(doc, (score, sections))| {
+                let best = sections
+                    .into_iter()
+                    .max_by_key(|&(section, weight)| (weight, std::cmp::Reverse(section)))
+                    .map_or(0, |(section, _)| section);
+                (doc, score, best)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(doc, _, section)| {
+                let document = &self.documents[doc];
+                let section = &document.sections[section];
+                Hit {
+                    document,
+                    section,
+                    snippet: snippet(&section.text, &words),
+                }
+            })
+            .collect()
+    }
+}
+
+// The text around the first word starting with any of the query words
+fn snippet(text: &str, words: &[String]) -> Snippet {
+    let lower = text.to_lowercase();
+    // Lower casing may change the length of the text, in which case we don't highlight
+    let found = if lower.len() == text.len() {
+        words
+            .iter()
+            .filter_map(|word| {
+                lower
+                    .match_indices(word.as_str())
+                    .find(|&(i, _)| is_word_start(&lower, i))
+                    .map(|(i, w)| (i, i + w.len()))
+            })
+            .min()
+    } else {
+        None
+    };
+    let (start, end) = match found {
+        Some(found) => found,
+        None => {
+            return Snippet {
+                before: truncate_end(text, SNIPPET_CONTEXT * 2),
+                ..Snippet::default()
+            }
+        }
+    };
+
+    let before = &text[..start];
+    let skip = before.chars().count().saturating_sub(SNIPPET_CONTEXT);
+    let before: String = before.chars().skip(skip).collect();
+    Snippet {
+        before: if skip > 0 {
+            format!("…{}", before)
+        } else {
+            before
+        },
+        matched: text[start..end].to_string(),
+        after: truncate_end(&text[end..], SNIPPET_CONTEXT),
+    }
+}
+
+fn is_word_start(text: &str, i: usize) -> bool {
+    text[..i]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !c.is_alphanumeric() && c != '_')
+}
+
+fn truncate_end(text: &str, chars: usize) -> String {
+    if text.chars().count() > chars {
+        format!("{}…", text.chars().take(chars).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two slides, the first one with a heading
+    fn index() -> SearchIndex {
+        serde_json::from_str(
+            r#"{
+                "documents": [
+                    {"deck": "rust", "deck_title": "Rust", "slug": "ownership", "title": "Ownership",
+                     "sections": [
+                        {"heading": null, "anchor": null, "text": "Every value has an owner"},
+                        {"heading": "Borrowing", "anchor": "borrowing", "text": "References borrow a value"}
+                     ]},
+                    {"deck": "rust", "deck_title": "Rust", "slug": "threads", "title": "Threads",
+                     "sections": [{"heading": null, "anchor": null, "text": "Move a value into a thread"}]}
+                ],
+                "terms": {
+                    "ownership": [[0, 0, 10]],
+                    "owner": [[0, 0, 1]],
+                    "borrowing": [[0, 1, 5]],
+                    "borrow": [[0, 1, 1]],
+                    "value": [[0, 0, 1], [0, 1, 1], [1, 0, 1]],
+                    "thread": [[1, 0, 1]],
+                    "threads": [[1, 0, 10]]
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn slugs(hits: &[Hit]) -> Vec<String> {
+        hits.iter().map(|hit| hit.document.slug.clone()).collect()
+    }
+
+    #[test]
+    fn ranks_by_weight() {
+        let index = index();
+        assert_eq!(
+            slugs(&index.search("value", 10)),
+            vec!["ownership", "threads"]
+        );
+        assert_eq!(slugs(&index.search("thread", 10)), vec!["threads"]);
+        assert_eq!(slugs(&index.search("value", 1)), vec!["ownership"]);
+    }
+
+    #[test]
+    fn words_match_prefixes_and_all_have_to_match() {
+        let index = index();
+        assert_eq!(slugs(&index.search("Own", 10)), vec!["ownership"]);
+        assert_eq!(slugs(&index.search("value thr", 10)), vec!["threads"]);
+        assert!(index.search("value lifetime", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn hits_point_at_the_best_section() {
+        let index = index();
+        let hits = index.search("borrow", 10);
+        assert_eq!(hits[0].section.anchor.as_deref(), Some("borrowing"));
+        assert_eq!(hits[0].snippet.matched, "borrow");
+    }
+
+    #[test]
+    fn snippet_highlights_the_first_word_start() {
+        let words = vec!["own".to_string()];
+        let snippet = snippet("Cows have an owner", &words);
+        assert_eq!(snippet.before, "Cows have an ");
+        assert_eq!(snippet.matched, "own");
+        assert_eq!(snippet.after, "er");
+    }
+
+    #[test]
+    fn snippet_trims_long_context() {
+        let text = format!("{} needle {}", "a".repeat(50), "b".repeat(50));
+        let snippet = snippet(&text, &["needle".to_string()]);
+        assert_eq!(snippet.before, format!("…{} ", "a".repeat(39)));
+        assert_eq!(snippet.matched, "needle");
+        assert_eq!(snippet.after, format!(" {}…", "b".repeat(39)));
+    }
+
+    #[test]
+    fn snippet_without_a_match_starts_the_text() {
+        let snippet = snippet("Nothing to see", &["needle".to_string()]);
+        assert_eq!(snippet.before, "Nothing to see");
+        assert!(snippet.matched.is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+use wasm_bindgen::JsValue;
+
+use crate::{lru::Lru, slides_data::SlideContent};
+
+/// How many fetched slides we keep
+pub const DEFAULT_CAPACITY: usize = 32;
+
+/// Fetched slides keyed by their URL, so going back to one or to a prefetched
+/// neighbour needs no request.
+/// The least recently used entry is evicted once the cache is full.
+#[derive(Debug)]
+pub struct SlideCache {
+    entries: Lru<String, SlideContent>,
+}
+
+impl SlideCache {
+    pub fn new(capacity: usize) -> Self {
+        SlideCache {
+            entries: Lru::new(capacity),
+        }
+    }
+
+    /// The content of the slide, which becomes the most recently used
+    pub fn get(&mut self, url: &str) -> Option<SlideContent> {
+        self.entries.get(url).cloned()
+    }
+
+    /// Whether the slide is cached, without counting as a use
+    pub fn contains(&self, url: &str) -> bool {
+        self.entries.contains(url)
+    }
+
+    pub fn insert(&mut self, url: &str, content: SlideContent) {
+        self.entries.insert(url.to_string(), content);
+    }
+}
+
+impl Default for SlideCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Whether the user asked to save data, e.g. on a metered connection, in which
+/// case nothing is fetched ahead.
+/// `navigator.connection` isn't available in every browser.
+pub fn saves_data() -> bool {
+    let navigator = yew::utils::window().navigator();
+    js_sys::Reflect::get(&navigator, &JsValue::from_str("connection"))
+        .ok()
+        .filter(|connection| connection.is_object())
+        .and_then(|connection| {
+            js_sys::Reflect::get(&connection, &JsValue::from_str("saveData")).ok()
+        })
+        .and_then(|save_data| save_data.as_bool())
+        .unwrap_or(false)
+}
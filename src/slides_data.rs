@@ -1,22 +1,394 @@
-#[derive(Debug, PartialEq)]
-pub struct Slide<'a> {
-    pub slug: &'a str,
-    pub tags: &'a [&'a str],
-    pub title: &'a str,
-    pub url: &'a str,
-}
-
-pub const SLIDES: &[Slide<'static>] = &[
-    Slide {
-        slug: "FirstAndForMost",
-        title: "First",
-        tags: &["Fist", "Post"],
-        url: "/api/slide/page_0.md",
-    },
-    Slide {
-        slug: "SecondAndMiddleMost",
-        title: "Second",
-        tags: &["Second", "Post"],
-        url: "/api/slide/page_1.md",
+use std::collections::BTreeMap;
+
+use ankan_common::{front_matter, markup::REFERENCES_SLUG};
+use serde::Deserialize;
+use yew::{
+    format::{Json, Nothing},
+    services::{
+        fetch::{FetchTask, Request, Response},
+        ConsoleService, FetchService,
     },
-];
+    Callback,
+};
+
+use crate::bibliography::BIBLIOGRAPHY_URL;
+
+/// The deck manifest, fetched once at startup.
+/// Deploying one lets slides be reordered or retitled without a rebuild,
+/// without it we use the manifest generated from static/api/slide by build.rs.
+pub const DECK_URL: &str = "/api/deck.json";
+
+/// How many slides either side of the one presented are fetched ahead,
+/// unless the deck says otherwise
+pub const DEFAULT_PREFETCH: usize = 2;
+
+const GENERATED_MANIFEST: &str = include_str!(concat!(env!("OUT_DIR"), "/deck.json"));
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Slide {
+    pub slug: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: Format,
+    /// Estimated time to present the slide, in seconds
+    #[serde(default)]
+    pub duration: Option<u32>,
+    /// An image URL or a CSS colour
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Extra CSS class for the slide
+    #[serde(default)]
+    pub class: Option<String>,
+    #[serde(default)]
+    pub transition: Transition,
+    /// Name of the section the slide starts, the slides after it belong to the section
+    /// until the next one starting a section
+    #[serde(default)]
+    pub section: Option<String>,
+    /// Fragments revealed one after the other on the slide, then on each sub-slide
+    /// beneath it. Without them the slide is shown whole.
+    #[serde(default)]
+    pub fragments: Vec<usize>,
+    /// Skipped when stepping through the deck, but it can still be linked to
+    #[serde(default)]
+    pub hidden: bool,
+    /// Not ready to be presented, only stepped through in preview mode
+    #[serde(default)]
+    pub draft: bool,
+    /// Slugs the slide used to have, links using them are redirected to it
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// How a slide comes into view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    #[default]
+    None,
+    Fade,
+    Slide,
+    Zoom,
+}
+
+/// What a slide is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Markdown,
+    Html,
+    /// Go by the extension of the URL, or the Content-Type of the response
+    #[default]
+    Detect,
+    /// The References slide, generated from the bibliography rather than fetched
+    References,
+}
+
+impl Slide {
+    /// Whether the slide is generated from the citations of the deck, so it has
+    /// nothing to fetch
+    pub fn is_references(&self) -> bool {
+        self.format == Format::References
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Whether the slide is stepped through and listed, hidden slides and drafts
+    /// only are in preview mode
+    pub fn is_listed(&self, preview: bool) -> bool {
+        preview || !(self.hidden || self.draft)
+    }
+
+    /// Inline style for the background, an image when it looks like a URL, otherwise a
+    /// colour or a gradient such as `rgb(...)` or `linear-gradient(...)`
+    pub fn background_style(&self) -> Option<String> {
+        let background = self.background.as_deref()?.trim();
+        if !is_safe_background(background) {
+            return None;
+        }
+        let is_image = background.starts_with('/')
+            || background.starts_with("http://")
+            || background.starts_with("https://")
+            || background.rsplit_once('.').is_some_and(|(_, ext)| {
+                matches!(ext, "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp")
+            });
+        Some(if is_image {
+            format!(
+                "background-image: url(\"{}\"); background-size: cover; background-position: center;",
+                background
+            )
+        } else {
+            format!("background: {};", background)
+        })
+    }
+
+    /// The format to render the slide with, never `Format::Detect`
+    pub fn format(&self, content_type: Option<&str>) -> Format {
+        if self.format != Format::Detect {
+            return self.format;
+        }
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        let extension = path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("md") | Some("markdown") => Format::Markdown,
+            Some("html") | Some("htm") => Format::Html,
+            _ => match content_type {
+                Some(mime) if mime.starts_with("text/html") => Format::Html,
+                _ => Format::Markdown,
+            },
+        }
+    }
+}
+
+/// The content of a slide as fetched from the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlideContent {
+    pub text: String,
+    pub content_type: Option<String>,
+}
+
+/// A talk, its slides are in the order they are presented
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Deck {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+    /// How many slides either side of the one presented are fetched ahead
+    #[serde(default)]
+    pub prefetch: Option<usize>,
+    /// Whether moving past the last slide carries on from the first, and back
+    #[serde(default, rename = "loop")]
+    pub loops: bool,
+    /// The keys the slides cite, in the order that numbers them
+    #[serde(default)]
+    pub citations: Vec<String>,
+    pub slides: Vec<Slide>,
+}
+
+impl Deck {
+    pub fn prefetch(&self) -> usize {
+        self.prefetch.unwrap_or(DEFAULT_PREFETCH)
+    }
+
+    /// Index of the slide with the slug, or the alias
+    pub fn slide_index(&self, slug: &str) -> Option<usize> {
+        self.slides
+            .iter()
+            .position(|slide| slide.slug == slug)
+            .or_else(|| {
+                self.slides
+                    .iter()
+                    .position(|slide| slide.aliases.iter().any(|alias| alias == slug))
+            })
+    }
+
+    /// Append the References slide, generated from the citations in the slides,
+    /// unless the deck already places it somewhere or cites nothing.
+    fn with_references(mut self) -> Self {
+        // A deck places it by listing the bibliography as one of its slides
+        for slide in &mut self.slides {
+            if slide.url == BIBLIOGRAPHY_URL {
+                slide.format = Format::References;
+            }
+        }
+        if !self.citations.is_empty() && !self.slides.iter().any(Slide::is_references) {
+            self.slides.push(Slide {
+                slug: REFERENCES_SLUG.to_string(),
+                title: "References".to_string(),
+                url: BIBLIOGRAPHY_URL.to_string(),
+                format: Format::References,
+                ..Slide::default()
+            });
+        }
+        self
+    }
+}
+
+/// Every deck we can present, the first one is the default
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Manifest {
+    pub decks: Vec<Deck>,
+}
+
+impl Manifest {
+    pub fn generated() -> Self {
+        serde_json::from_str(GENERATED_MANIFEST).expect("build.rs generates a valid manifest")
+    }
+
+    /// Every slide of every deck, with the deck it belongs to
+    pub fn slides(&self) -> impl Iterator<Item = (&Deck, &Slide)> {
+        self.decks
+            .iter()
+            .flat_map(|deck| deck.slides.iter().map(move |slide| (deck, slide)))
+    }
+
+    /// Every tag in use, with the number of slides carrying it. Hidden slides and
+    /// drafts only count in preview mode.
+    pub fn tags(&self, preview: bool) -> BTreeMap<&str, usize> {
+        let mut tags = BTreeMap::new();
+        for (_, slide) in self.slides().filter(|(_, slide)| slide.is_listed(preview)) {
+            for tag in &slide.tags {
+                *tags.entry(tag.as_str()).or_insert(0) += 1;
+            }
+        }
+        tags
+    }
+
+    pub fn deck_index(&self, id: &str) -> Option<usize> {
+        self.decks.iter().position(|deck| deck.id == id)
+    }
+
+    fn with_references(mut self) -> Self {
+        self.decks = self.decks.into_iter().map(Deck::with_references).collect();
+        self
+    }
+
+    /// Drop the backgrounds that can't be put in a style, saying which
+    fn with_safe_backgrounds(mut self) -> Self {
+        for slide in self
+            .decks
+            .iter_mut()
+            .flat_map(|deck| deck.slides.iter_mut())
+        {
+            let background = match &slide.background {
+                Some(background) if !is_safe_background(background.trim()) => background,
+                _ => continue,
+            };
+            ConsoleService::warn(&format!(
+                "Ignoring the background of slide '{}', it may not hold quotes, backslashes, ';' or url(): {}",
+                slide.slug, background
+            ));
+            slide.background = None;
+        }
+        self
+    }
+}
+
+// Whether a background keeps to the declaration it is put in and loads nothing but
+// the image we wrap it in
+fn is_safe_background(background: &str) -> bool {
+    !background.contains([';', '"', '\'', '\\'])
+        && !background.to_ascii_lowercase().contains("url(")
+}
+
+pub fn fetch_manifest(callback: Callback<Result<Manifest, anyhow::Error>>) -> FetchTask {
+    let request = Request::get(DECK_URL)
+        .body(Nothing)
+        .expect("Could not build request.");
+    let callback = Callback::from(
+        move |response: Response<Json<Result<Manifest, anyhow::Error>>>| {
+            let status = response.status();
+            // The workers site and trunk serve answer a missing asset with index.html
+            let is_json = response
+                .headers()
+                .get("content-type")
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.contains("json"));
+            let Json(body) = response.into_body();
+            let manifest = if status.as_u16() == 404 || (status.is_success() && !is_json) {
+                ConsoleService::info(&format!(
+                    "No deck manifest at {}, using the generated one",
+                    DECK_URL
+                ));
+                Manifest::generated()
+            } else if !status.is_success() {
+                callback.emit(Err(anyhow::anyhow!(
+                    "The deck manifest could not be fetched, the server answered with status {}",
+                    status.as_u16()
+                )));
+                return;
+            } else {
+                // A manifest that is there but broken is a mistake to point out
+                match body {
+                    Ok(manifest) => manifest,
+                    Err(error) => {
+                        callback.emit(Err(error.context("The deck manifest is invalid")));
+                        return;
+                    }
+                }
+            };
+            if manifest.decks.is_empty() {
+                callback.emit(Err(anyhow::anyhow!("The deck manifest lists no decks")));
+            } else {
+                callback.emit(Ok(manifest.with_references().with_safe_backgrounds()));
+            }
+        },
+    );
+    FetchService::fetch(request, callback).expect("failed to start request")
+}
+
+/// Why a slide couldn't be fetched
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// No response at all, e.g. when offline
+    Network(String),
+    NotFound,
+    /// Any other status that isn't a success
+    Server(u16),
+    /// The body couldn't be read as text
+    Parse(String),
+}
+
+impl FetchError {
+    /// Whether asking again later might work
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Network(_) => true,
+            FetchError::Server(status) => *status >= 500 || *status == 408 || *status == 429,
+            FetchError::NotFound | FetchError::Parse(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Network(error) => write!(f, "The server could not be reached: {}", error),
+            FetchError::NotFound => write!(f, "The slide was not found"),
+            FetchError::Server(status) => write!(f, "The server answered with status {}", status),
+            FetchError::Parse(error) => write!(f, "The slide could not be read: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Fetch the content of a slide, without its front matter
+pub fn fetch_text(url: &str, callback: Callback<Result<SlideContent, FetchError>>) -> FetchTask {
+    let request = Request::get(url)
+        .body(Nothing)
+        .expect("Could not build request.");
+    let callback = Callback::from(move |response: Response<Result<String, anyhow::Error>>| {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        // Without any headers there was no response, whatever the status says
+        let responded = !response.headers().is_empty();
+        let content = match response.into_body() {
+            Err(error) if responded => Err(FetchError::Parse(error.to_string())),
+            Err(error) => Err(FetchError::Network(error.to_string())),
+            // Error pages aren't slides
+            Ok(_) if status.as_u16() == 404 || status.as_u16() == 410 => Err(FetchError::NotFound),
+            Ok(_) if !status.is_success() => Err(FetchError::Server(status.as_u16())),
+            Ok(text) => Ok(SlideContent {
+                text: front_matter::strip(&text).to_string(),
+                content_type,
+            }),
+        };
+        callback.emit(content)
+    });
+    FetchService::fetch(request, callback).expect("failed to start request")
+}
@@ -4,10 +4,24 @@ use yew_router::{prelude::*, switch::Permissive};
 
 #[derive(Debug, Switch, Clone)]
 pub enum AppRoute {
+    #[to = "/decks/{deck}/slides/{num}"]
+    DeckSlidesNumber(String, usize),
+    #[to = "/decks/{deck}/slides/{name}"]
+    DeckSlidesName(String, String),
+    #[to = "/decks/{deck}"]
+    Deck(String),
     #[to = "/slides/{num}"]
     SlidesNumber(usize),
     #[to = "/slides/{name}"]
     SlidesName(String),
+    #[to = "/tags/{tag}/decks/{deck}/slides/{name}"]
+    TagSlides(String, String, String),
+    #[to = "/tags/{tag}"]
+    Tag(String),
+    #[to = "/tags"]
+    Tags,
+    #[to = "/glossary"]
+    Glossary,
     #[to = "/page-not-found"]
     PageNotFound(Permissive<String>),
     #[to = "/!"]
@@ -15,6 +29,16 @@ pub enum AppRoute {
 }
 
 impl AppRoute {
+    /// The slides carrying the tag, which is percent-encoded into the path
+    pub fn tag(tag: &str) -> Self {
+        AppRoute::Tag(encode(tag))
+    }
+
+    /// A slide reached by browsing a tag, which is percent-encoded into the path
+    pub fn tag_slides(tag: &str, deck: String, name: String) -> Self {
+        AppRoute::TagSlides(encode(tag), deck, name)
+    }
+
     pub fn into_switch(self) -> UrlSwitch {
         UrlSwitch(self)
     }
@@ -24,6 +48,11 @@ impl AppRoute {
     }
 }
 
+// Tags may hold spaces, slashes and such, app.rs decodes them on the way in
+fn encode(part: &str) -> String {
+    js_sys::encode_uri_component(part).into()
+}
+
 /// Helper type which wraps the AppRoute enum but handles prefixes.
 /// It makes it possible to host this sight in other locations such as '/foo/'
 /// instead of simply '/'
@@ -0,0 +1,186 @@
+/// A minimal zip writer, just enough to bundle a few text files into an archive.
+///
+/// Entries are stored without compression, which keeps the wasm binary small
+/// and the archives we generate (a Cargo.toml and a main.rs) are tiny anyway.
+pub struct ZipWriter {
+    buffer: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+// MS-DOS date for 1980-01-01, the earliest date a zip entry can hold
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        ZipWriter {
+            buffer: vec![],
+            central_directory: vec![],
+            entries: 0,
+        }
+    }
+
+    pub fn add_file(&mut self, name: &str, data: &[u8]) {
+        let offset = self.buffer.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name = name.as_bytes();
+
+        // Local file header
+        put_u32(&mut self.buffer, 0x0403_4b50);
+        put_u16(&mut self.buffer, 20); // version needed to extract
+        put_u16(&mut self.buffer, 0); // flags
+        put_u16(&mut self.buffer, 0); // compression: stored
+        put_u16(&mut self.buffer, 0); // modification time
+        put_u16(&mut self.buffer, DOS_DATE);
+        put_u32(&mut self.buffer, crc);
+        put_u32(&mut self.buffer, size); // compressed size
+        put_u32(&mut self.buffer, size); // uncompressed size
+        put_u16(&mut self.buffer, name.len() as u16);
+        put_u16(&mut self.buffer, 0); // extra field length
+        self.buffer.extend_from_slice(name);
+        self.buffer.extend_from_slice(data);
+
+        // Central directory record
+        let cd = &mut self.central_directory;
+        put_u32(cd, 0x0201_4b50);
+        put_u16(cd, 20); // version made by
+        put_u16(cd, 20); // version needed to extract
+        put_u16(cd, 0); // flags
+        put_u16(cd, 0); // compression: stored
+        put_u16(cd, 0); // modification time
+        put_u16(cd, DOS_DATE);
+        put_u32(cd, crc);
+        put_u32(cd, size);
+        put_u32(cd, size);
+        put_u16(cd, name.len() as u16);
+        put_u16(cd, 0); // extra field length
+        put_u16(cd, 0); // comment length
+        put_u16(cd, 0); // disk number
+        put_u16(cd, 0); // internal attributes
+        put_u32(cd, 0); // external attributes
+        put_u32(cd, offset);
+        cd.extend_from_slice(name);
+
+        self.entries += 1;
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let cd_offset = self.buffer.len() as u32;
+        let cd_size = self.central_directory.len() as u32;
+        self.buffer.append(&mut self.central_directory);
+
+        // End of central directory record
+        put_u32(&mut self.buffer, 0x0605_4b50);
+        put_u16(&mut self.buffer, 0); // this disk
+        put_u16(&mut self.buffer, 0); // disk with the central directory
+        put_u16(&mut self.buffer, self.entries);
+        put_u16(&mut self.buffer, self.entries);
+        put_u32(&mut self.buffer, cd_size);
+        put_u32(&mut self.buffer, cd_offset);
+        put_u16(&mut self.buffer, 0); // comment length
+        self.buffer
+    }
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn local_headers_hold_the_files() {
+        let mut zip = ZipWriter::new();
+        zip.add_file("a.txt", b"hello");
+        zip.add_file("b/c.rs", b"fn main() {}");
+        let bytes = zip.finish();
+
+        assert_eq!(u32_at(&bytes, 0), 0x0403_4b50);
+        assert_eq!(u32_at(&bytes, 14), crc32(b"hello"));
+        assert_eq!(u32_at(&bytes, 18), 5);
+        assert_eq!(u32_at(&bytes, 22), 5);
+        assert_eq!(u16_at(&bytes, 26), 5);
+        assert_eq!(&bytes[30..35], b"a.txt");
+        assert_eq!(&bytes[35..40], b"hello");
+
+        // The second entry starts right after the first one's data
+        assert_eq!(u32_at(&bytes, 40), 0x0403_4b50);
+        assert_eq!(&bytes[70..76], b"b/c.rs");
+        assert_eq!(&bytes[76..88], b"fn main() {}");
+    }
+
+    #[test]
+    fn central_directory_points_at_the_local_headers() {
+        let mut zip = ZipWriter::new();
+        zip.add_file("a.txt", b"hello");
+        zip.add_file("b/c.rs", b"fn main() {}");
+        let bytes = zip.finish();
+
+        let eocd = bytes.len() - 22;
+        assert_eq!(u32_at(&bytes, eocd), 0x0605_4b50);
+        assert_eq!(u16_at(&bytes, eocd + 8), 2);
+        assert_eq!(u16_at(&bytes, eocd + 10), 2);
+        let cd_size = u32_at(&bytes, eocd + 12) as usize;
+        let cd_offset = u32_at(&bytes, eocd + 16) as usize;
+        assert_eq!(cd_offset, 88);
+        assert_eq!(cd_offset + cd_size, eocd);
+
+        // Each record is 46 bytes followed by the name
+        let first = cd_offset;
+        assert_eq!(u32_at(&bytes, first), 0x0201_4b50);
+        assert_eq!(u32_at(&bytes, first + 16), crc32(b"hello"));
+        assert_eq!(u32_at(&bytes, first + 42), 0);
+        assert_eq!(&bytes[first + 46..first + 51], b"a.txt");
+        let second = first + 51;
+        assert_eq!(u32_at(&bytes, second), 0x0201_4b50);
+        assert_eq!(u32_at(&bytes, second + 42), 40);
+        assert_eq!(&bytes[second + 46..second + 52], b"b/c.rs");
+        assert_eq!(second + 52, eocd);
+    }
+
+    #[test]
+    fn an_empty_archive_is_just_the_end_record() {
+        let bytes = ZipWriter::new().finish();
+        assert_eq!(bytes.len(), 22);
+        assert_eq!(u16_at(&bytes, 8), 0);
+        assert_eq!(u32_at(&bytes, 16), 0);
+    }
+}